@@ -9,6 +9,8 @@ public interface ILifeEngine
     void ActivateCell(int x, int y);
     
     IEnumerable<Cell> GetActiveCells();
+
+    IEnumerable<Cell> GetActiveCells(Cell topLeft, Cell bottomRight);
     
     void GenerateRandomSquare(Cell topLeft, uint size);
 
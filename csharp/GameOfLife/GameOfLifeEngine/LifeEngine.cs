@@ -116,6 +116,12 @@ public class LifeEngine : ILifeEngine
         return _activeCells;
     }
 
+    public IEnumerable<Cell> GetActiveCells(Cell topLeft, Cell bottomRight)
+    {
+        return _activeCells.Where(c =>
+            c.X >= topLeft.X && c.X <= bottomRight.X && c.Y >= topLeft.Y && c.Y <= bottomRight.Y);
+    }
+
     public int GetActiveCellCount()
     {
         return _activeCells.Count;
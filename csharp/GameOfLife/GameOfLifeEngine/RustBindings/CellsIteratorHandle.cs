@@ -11,13 +11,23 @@ internal sealed class CellsIteratorHandle : SafeHandle
 
     public void Init(EngineHandle engine)
     {
-        SetHandle(EngineNativeMethods.engine_alive_cells_iterator_get(engine.DangerousGetHandle()));
+        EngineNativeMethods.Check(
+            EngineNativeMethods.engine_alive_cells_iterator_get(engine.DangerousGetHandle(), out var iterator));
+        SetHandle(iterator);
+    }
+
+    // Gets the next cell of the snapshot taken by Init, or false once all were seen.
+    public bool TryNext(out NativeCell cell)
+    {
+        EngineNativeMethods.Check(
+            EngineNativeMethods.engine_alive_cells_iterator_next(handle, out cell, out var hasCell));
+        return hasCell;
     }
 
     protected override bool ReleaseHandle()
     {
-        EngineNativeMethods.engine_alive_cells_iterator_free(handle);
+        var status = EngineNativeMethods.engine_alive_cells_iterator_free(handle);
         SetHandle(IntPtr.Zero);
-        return true;
+        return status == EngineStatus.Ok;
     }
 }
\ No newline at end of file
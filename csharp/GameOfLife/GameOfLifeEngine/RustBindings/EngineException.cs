@@ -0,0 +1,7 @@
+﻿namespace GameOfLife.RustBindings;
+
+// Raised when a call into the Rust engine fails, with the message the engine gave.
+public class EngineException(EngineStatus status, string message) : Exception(message)
+{
+    public EngineStatus Status { get; } = status;
+}
\ No newline at end of file
@@ -9,16 +9,23 @@ internal sealed class EngineHandle : SafeHandle
 
     public void Init(uint cols, uint rows)
     {
-        SetHandle(EngineNativeMethods.engine_new(cols, rows));
+        EngineNativeMethods.Check(EngineNativeMethods.engine_get_version(out var abiVersion, out _));
+        if (abiVersion != EngineNativeMethods.AbiVersion)
+        {
+            throw new InvalidOperationException(
+                $"game_of_life_ffi has ABI version {abiVersion}, expected {EngineNativeMethods.AbiVersion}");
+        }
+        EngineNativeMethods.Check(EngineNativeMethods.engine_new(cols, rows, out var engine));
+        SetHandle(engine);
     }
 
     public override bool IsInvalid => handle == IntPtr.Zero;
 
     protected override bool ReleaseHandle()
     {
-        EngineNativeMethods.engine_free(handle);
+        var status = EngineNativeMethods.engine_free(handle);
         // After freeing, set to zero to avoid double-free on finalization
         SetHandle(IntPtr.Zero);
-        return true;
+        return status == EngineStatus.Ok;
     }
 }
\ No newline at end of file
@@ -6,35 +6,138 @@ internal static class EngineNativeMethods
 {
     private const string DllName = "game_of_life_ffi";
 
+    // ABI version of game-of-life-ffi these bindings are written against
+    internal const uint AbiVersion = 1;
+
+    // Version
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_get_version(out uint abiVersion, out uint capabilities);
+
     // Engine lifecycle
     [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
-    internal static extern IntPtr engine_new(uint cols, uint rows);
+    internal static extern EngineStatus engine_new(uint cols, uint rows, out IntPtr engine);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_free(IntPtr engine);
+
+    // World settings
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_set_rule(IntPtr engine, byte[] rule, nuint length);
 
     [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
-    internal static extern void engine_free(IntPtr engine);
+    internal static extern EngineStatus engine_get_rule(IntPtr engine, [Out] byte[] buffer, nuint capacity,
+        out nuint length);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_set_topology(IntPtr engine, EngineTopology topology);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_get_topology(IntPtr engine, out EngineTopology topology);
 
     // Engine operations
     [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
-    internal static extern void engine_next(IntPtr engine);
+    internal static extern EngineStatus engine_next(IntPtr engine);
 
     [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
-    internal static extern void engine_activate_cell(IntPtr engine, uint x, uint y);
+    internal static extern EngineStatus engine_step(IntPtr engine, ulong steps);
+
+    // Called by engine_step after each step; returning non-zero stops the run
+    [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+    internal delegate int StepObserver(IntPtr userData, ulong generation, nuint population);
 
-    // Iterator over alive cells
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_set_step_observer(IntPtr engine, StepObserver? observer,
+        IntPtr userData);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_get_generation(IntPtr engine, out ulong generation);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_deactivate_cell(IntPtr engine, uint x, uint y);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_is_cell_alive(IntPtr engine, uint x, uint y,
+        [MarshalAs(UnmanagedType.U1)] out bool isAlive);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_clear(IntPtr engine);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_clear_rect(IntPtr engine, uint x, uint y, uint width, uint height);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_get_population(IntPtr engine, out nuint population);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_get_bounding_box(IntPtr engine, out NativeRegion boundingBox,
+        [MarshalAs(UnmanagedType.U1)] out bool hasCells);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_activate_cell(IntPtr engine, uint x, uint y);
+
+    // Bulk copies of alive cells: count is the number of cells, which are only copied as far as capacity allows
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_copy_alive_cells(IntPtr engine, [Out] NativeCell[] buffer,
+        nuint capacity, out nuint count);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_copy_alive_cells_in_rect(IntPtr engine, uint x, uint y, uint width,
+        uint height, [Out] NativeCell[] buffer, nuint capacity, out nuint count);
+
+    // Iterator over a snapshot of the alive cells, unaffected by later changes to the engine
     [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true,
         EntryPoint = "engine_alive_cells_iterator_get")]
-    internal static extern IntPtr engine_alive_cells_iterator_get(IntPtr engine);
+    internal static extern EngineStatus engine_alive_cells_iterator_get(IntPtr engine, out IntPtr it);
 
     [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true,
         EntryPoint = "engine_alive_cells_iterator_free")]
-    internal static extern void engine_alive_cells_iterator_free(IntPtr it);
+    internal static extern EngineStatus engine_alive_cells_iterator_free(IntPtr it);
 
     [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true,
         EntryPoint = "engine_alive_cells_iterator_next")]
-    internal static extern IntPtr engine_alive_cells_iterator_next(IntPtr it);
+    internal static extern EngineStatus engine_alive_cells_iterator_next(IntPtr it, out NativeCell cell,
+        [MarshalAs(UnmanagedType.U1)] out bool hasCell);
 
     [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true,
         EntryPoint = "engine_generate_random_square")]
-    internal static extern void engine_generate_random_square(IntPtr engine, uint topLeftX, uint topLeftY,
+    internal static extern EngineStatus engine_generate_random_square(IntPtr engine, uint topLeftX, uint topLeftY,
         uint size);
+
+    // History of edits and generations, disabled until engine_set_history enables it
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_set_history(IntPtr engine,
+        [MarshalAs(UnmanagedType.U1)] bool isEnabled);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_undo(IntPtr engine, [MarshalAs(UnmanagedType.U1)] out bool done);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_redo(IntPtr engine, [MarshalAs(UnmanagedType.U1)] out bool done);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_step_back(IntPtr engine,
+        [MarshalAs(UnmanagedType.U1)] out bool done);
+
+    // Patterns as RLE text
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_load_rle(IntPtr engine, byte[] data, nuint length, uint x, uint y);
+
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern EngineStatus engine_save_rle(IntPtr engine, [Out] byte[] buffer, nuint capacity,
+        out nuint length);
+
+    // Errors
+    [DllImport(DllName, CallingConvention = CallingConvention.StdCall, ExactSpelling = true)]
+    internal static extern IntPtr engine_last_error_message();
+
+    // Throws an EngineException carrying the engine's message when a call did not succeed.
+    internal static void Check(EngineStatus status)
+    {
+        if (status == EngineStatus.Ok)
+        {
+            return;
+        }
+        var message = Marshal.PtrToStringUTF8(engine_last_error_message()) ?? status.ToString();
+        throw new EngineException(status, message);
+    }
 }
\ No newline at end of file
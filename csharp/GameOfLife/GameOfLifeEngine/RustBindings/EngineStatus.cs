@@ -0,0 +1,10 @@
+﻿namespace GameOfLife.RustBindings;
+
+// Mirrors EngineStatus in game-of-life-ffi.
+public enum EngineStatus
+{
+    Ok = 0,
+    NullPointer = 1,
+    InvalidArgument = 2,
+    Panic = 3,
+}
\ No newline at end of file
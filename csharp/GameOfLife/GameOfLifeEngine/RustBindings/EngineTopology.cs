@@ -0,0 +1,8 @@
+﻿namespace GameOfLife.RustBindings;
+
+// Mirrors the ENGINE_TOPOLOGY_* constants in game-of-life-ffi.
+public enum EngineTopology : uint
+{
+    Bounded = 0,
+    Torus = 1,
+}
\ No newline at end of file
@@ -0,0 +1,12 @@
+﻿using System.Runtime.InteropServices;
+
+namespace GameOfLife.RustBindings;
+
+[StructLayout(LayoutKind.Sequential)]
+public struct NativeRegion
+{
+    public uint x;
+    public uint y;
+    public uint width;
+    public uint height;
+}
\ No newline at end of file
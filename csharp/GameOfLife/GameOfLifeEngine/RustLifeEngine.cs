@@ -1,4 +1,5 @@
-﻿using System.Runtime.InteropServices;
+﻿using System.Runtime.ExceptionServices;
+using System.Text;
 using GameOfLife.RustBindings;
 
 namespace GameOfLife;
@@ -9,6 +10,11 @@ public class RustLifeEngine : ILifeEngine, IDisposable
     public int Rows { get; }
 
     private readonly EngineHandle _engineHandle;
+    // Reused by every bulk copy of alive cells, grown when the engine reports more cells than fit.
+    private NativeCell[] _cellsBuffer = [];
+
+    private delegate EngineStatus CopyCellsCall(NativeCell[] buffer, nuint capacity, out nuint count);
+    private delegate EngineStatus CopyBytesCall(byte[] buffer, nuint capacity, out nuint length);
 
     public RustLifeEngine(int cols, int rows)
     {
@@ -18,35 +24,256 @@ public class RustLifeEngine : ILifeEngine, IDisposable
         _engineHandle.Init((uint)cols, (uint)rows);
     }
 
+    // Rulestring such as "B3/S23"; the legacy "23/3" notation is accepted too.
+    public string Rule
+    {
+        get => CopyString((byte[] buffer, nuint capacity, out nuint length) =>
+            EngineNativeMethods.engine_get_rule(_engineHandle.DangerousGetHandle(), buffer, capacity, out length));
+        set
+        {
+            var bytes = Encoding.UTF8.GetBytes(value);
+            EngineNativeMethods.Check(EngineNativeMethods.engine_set_rule(_engineHandle.DangerousGetHandle(), bytes,
+                (nuint)bytes.Length));
+        }
+    }
+
+    public EngineTopology Topology
+    {
+        get
+        {
+            EngineNativeMethods.Check(
+                EngineNativeMethods.engine_get_topology(_engineHandle.DangerousGetHandle(), out var topology));
+            return topology;
+        }
+        set => EngineNativeMethods.Check(
+            EngineNativeMethods.engine_set_topology(_engineHandle.DangerousGetHandle(), value));
+    }
+
+    public ulong Generation
+    {
+        get
+        {
+            EngineNativeMethods.Check(
+                EngineNativeMethods.engine_get_generation(_engineHandle.DangerousGetHandle(), out var generation));
+            return generation;
+        }
+    }
+
+    public long Population
+    {
+        get
+        {
+            EngineNativeMethods.Check(
+                EngineNativeMethods.engine_get_population(_engineHandle.DangerousGetHandle(), out var population));
+            return (long)population;
+        }
+    }
+
     public void Next()
     {
-        EngineNativeMethods.engine_next(_engineHandle.DangerousGetHandle());
+        EngineNativeMethods.Check(EngineNativeMethods.engine_next(_engineHandle.DangerousGetHandle()));
+    }
+
+    public void Step(ulong steps)
+    {
+        EngineNativeMethods.Check(EngineNativeMethods.engine_step(_engineHandle.DangerousGetHandle(), steps));
+    }
+
+    // Advances up to `steps` generations in one call, passing the generation and population to onStep after
+    // each one, e.g. to report progress. Returning false from onStep stops the run. Returns the number of
+    // generations advanced.
+    public ulong Run(ulong steps, Func<ulong, long, bool> onStep)
+    {
+        var engine = _engineHandle.DangerousGetHandle();
+        Exception? error = null;
+        EngineNativeMethods.StepObserver observer = (_, generation, population) =>
+        {
+            try
+            {
+                return onStep(generation, (long)population) ? 0 : 1;
+            }
+            catch (Exception e)
+            {
+                // Exceptions must not unwind through the engine, so they are rethrown once it returns.
+                error = e;
+                return 1;
+            }
+        };
+        var start = Generation;
+        EngineNativeMethods.Check(EngineNativeMethods.engine_set_step_observer(engine, observer, IntPtr.Zero));
+        try
+        {
+            EngineNativeMethods.Check(EngineNativeMethods.engine_step(engine, steps));
+        }
+        finally
+        {
+            EngineNativeMethods.Check(EngineNativeMethods.engine_set_step_observer(engine, null, IntPtr.Zero));
+            GC.KeepAlive(observer);
+        }
+        if (error != null)
+        {
+            ExceptionDispatchInfo.Throw(error);
+        }
+        return Generation - start;
     }
+
     public void ActivateCell(int x, int y)
     {
-        EngineNativeMethods.engine_activate_cell(_engineHandle.DangerousGetHandle(), (uint)x, (uint)y);
+        EngineNativeMethods.Check(
+            EngineNativeMethods.engine_activate_cell(_engineHandle.DangerousGetHandle(), (uint)x, (uint)y));
+    }
+
+    public void DeactivateCell(int x, int y)
+    {
+        EngineNativeMethods.Check(
+            EngineNativeMethods.engine_deactivate_cell(_engineHandle.DangerousGetHandle(), (uint)x, (uint)y));
+    }
+
+    public bool IsCellAlive(int x, int y)
+    {
+        EngineNativeMethods.Check(EngineNativeMethods.engine_is_cell_alive(_engineHandle.DangerousGetHandle(),
+            (uint)x, (uint)y, out var isAlive));
+        return isAlive;
+    }
+
+    public void Clear()
+    {
+        EngineNativeMethods.Check(EngineNativeMethods.engine_clear(_engineHandle.DangerousGetHandle()));
+    }
+
+    // Clears the cells between two corners, inclusive.
+    public void Clear(Cell topLeft, Cell bottomRight)
+    {
+        var width = (uint)Math.Max(bottomRight.X - topLeft.X + 1, 0);
+        var height = (uint)Math.Max(bottomRight.Y - topLeft.Y + 1, 0);
+        EngineNativeMethods.Check(EngineNativeMethods.engine_clear_rect(_engineHandle.DangerousGetHandle(),
+            (uint)topLeft.X, (uint)topLeft.Y, width, height));
+    }
+
+    // Smallest rectangle containing every alive cell, or null when there are none.
+    public NativeRegion? GetBoundingBox()
+    {
+        EngineNativeMethods.Check(EngineNativeMethods.engine_get_bounding_box(_engineHandle.DangerousGetHandle(),
+            out var boundingBox, out var hasCells));
+        return hasCells ? boundingBox : null;
+    }
+
+    // Undo, redo and stepping back only do something once the history is enabled.
+    public void SetHistory(bool isEnabled)
+    {
+        EngineNativeMethods.Check(
+            EngineNativeMethods.engine_set_history(_engineHandle.DangerousGetHandle(), isEnabled));
+    }
+
+    public bool Undo()
+    {
+        EngineNativeMethods.Check(EngineNativeMethods.engine_undo(_engineHandle.DangerousGetHandle(), out var done));
+        return done;
+    }
+
+    public bool Redo()
+    {
+        EngineNativeMethods.Check(EngineNativeMethods.engine_redo(_engineHandle.DangerousGetHandle(), out var done));
+        return done;
+    }
+
+    public bool StepBack()
+    {
+        EngineNativeMethods.Check(
+            EngineNativeMethods.engine_step_back(_engineHandle.DangerousGetHandle(), out var done));
+        return done;
+    }
+
+    // Adds the cells of an RLE pattern with its top left corner at the given cell, switching to its rule if it has one.
+    // Undo removes the cells again but keeps the rule.
+    public void LoadRle(string rle, Cell topLeft)
+    {
+        var bytes = Encoding.UTF8.GetBytes(rle);
+        EngineNativeMethods.Check(EngineNativeMethods.engine_load_rle(_engineHandle.DangerousGetHandle(), bytes,
+            (nuint)bytes.Length, (uint)topLeft.X, (uint)topLeft.Y));
+    }
+
+    // The alive cells as an RLE pattern, starting at the top left corner of the bounding box.
+    public string SaveRle()
+    {
+        return CopyString((byte[] buffer, nuint capacity, out nuint length) =>
+            EngineNativeMethods.engine_save_rle(_engineHandle.DangerousGetHandle(), buffer, capacity, out length));
     }
 
     public IEnumerable<Cell> GetActiveCells()
     {
-        return GetActiveNativeCells().Select(c => new Cell((int)c.x, (int)c.y));
+        return ToCells(GetActiveNativeCells());
     }
-    
-    public IEnumerable<NativeCell> GetActiveNativeCells()
+
+    public IEnumerable<Cell> GetActiveCells(Cell topLeft, Cell bottomRight)
+    {
+        return ToCells(GetActiveNativeCells(topLeft, bottomRight));
+    }
+
+    // Copies all alive cells in one call. The span is only valid until the next copy.
+    public ReadOnlySpan<NativeCell> GetActiveNativeCells()
+    {
+        var engine = _engineHandle.DangerousGetHandle();
+        return CopyCells((NativeCell[] buffer, nuint capacity, out nuint count) =>
+            EngineNativeMethods.engine_copy_alive_cells(engine, buffer, capacity, out count));
+    }
+
+    // Copies the alive cells between two corners, inclusive, in one call. The span is only valid until the next copy.
+    public ReadOnlySpan<NativeCell> GetActiveNativeCells(Cell topLeft, Cell bottomRight)
+    {
+        var engine = _engineHandle.DangerousGetHandle();
+        var width = (uint)Math.Max(bottomRight.X - topLeft.X + 1, 0);
+        var height = (uint)Math.Max(bottomRight.Y - topLeft.Y + 1, 0);
+        return CopyCells((NativeCell[] buffer, nuint capacity, out nuint count) =>
+            EngineNativeMethods.engine_copy_alive_cells_in_rect(engine, (uint)topLeft.X, (uint)topLeft.Y, width,
+                height, buffer, capacity, out count));
+    }
+
+    // Enumerates the alive cells one call at a time, as they were when the enumeration started,
+    // so the engine can be stepped or edited meanwhile.
+    public IEnumerable<NativeCell> EnumerateActiveNativeCells()
     {
         using var iteratorHandle = new CellsIteratorHandle();
         iteratorHandle.Init(_engineHandle);
-        var next = EngineNativeMethods.engine_alive_cells_iterator_next(iteratorHandle.DangerousGetHandle());
-        while (next != IntPtr.Zero)
+        while (iteratorHandle.TryNext(out var cell))
+        {
+            yield return cell;
+        }
+    }
+
+    private ReadOnlySpan<NativeCell> CopyCells(CopyCellsCall copy)
+    {
+        EngineNativeMethods.Check(copy(_cellsBuffer, (nuint)_cellsBuffer.Length, out var count));
+        if (count > (nuint)_cellsBuffer.Length)
+        {
+            _cellsBuffer = new NativeCell[Math.Max((int)count, _cellsBuffer.Length * 2)];
+            EngineNativeMethods.Check(copy(_cellsBuffer, (nuint)_cellsBuffer.Length, out count));
+        }
+        return _cellsBuffer.AsSpan(0, (int)count);
+    }
+
+    private static string CopyString(CopyBytesCall copy)
+    {
+        EngineNativeMethods.Check(copy([], 0, out var length));
+        var buffer = new byte[(int)length];
+        EngineNativeMethods.Check(copy(buffer, (nuint)buffer.Length, out length));
+        return Encoding.UTF8.GetString(buffer, 0, (int)length);
+    }
+
+    private static Cell[] ToCells(ReadOnlySpan<NativeCell> nativeCells)
+    {
+        var cells = new Cell[nativeCells.Length];
+        for (var i = 0; i < nativeCells.Length; i++)
         {
-            yield return Marshal.PtrToStructure<NativeCell>(next);
-            next = EngineNativeMethods.engine_alive_cells_iterator_next(iteratorHandle.DangerousGetHandle());
+            cells[i] = new Cell((int)nativeCells[i].x, (int)nativeCells[i].y);
         }
+        return cells;
     }
 
     public void GenerateRandomSquare(Cell topLeft, uint size)
     {
-        EngineNativeMethods.engine_generate_random_square(_engineHandle.DangerousGetHandle(), (uint)topLeft.X, (uint)topLeft.Y, size);
+        EngineNativeMethods.Check(EngineNativeMethods.engine_generate_random_square(
+            _engineHandle.DangerousGetHandle(), (uint)topLeft.X, (uint)topLeft.Y, size));
     }
 
     public void Dispose()
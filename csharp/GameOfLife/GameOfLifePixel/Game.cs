@@ -191,13 +191,8 @@ public class Game
 
         var topLeft = ToCell(topLeftWorld);
         var bottomRight = ToCell(bottomRightWorld);
-        foreach (var cell in _engine.GetActiveCells())
+        foreach (var cell in _engine.GetActiveCells(topLeft, bottomRight))
         {
-            if (cell.X < topLeft.X || cell.X > bottomRight.X || cell.Y < topLeft.Y || cell.Y > bottomRight.Y)
-            {
-                continue;
-            }
-
             var px = (int)(cell.X * Config.CellSize);
             var py = (int)(cell.Y * Config.CellSize);
             Raylib.DrawRectangle(px, py, Config.CellSizeInt, Config.CellSizeInt, Color.White);
@@ -1,75 +1,358 @@
 import init , { LifeEngineWrapper } from '/rust/game-of-life-wasm/pkg/game_of_life_wasm.js';
 
 const CELL_SIZE = 10;
-const WORLD_COLS = 80;
-const WORLD_ROWS = 60;
+// Size of the grid unless the page is opened from a shared link, which carries its own.
+const DEFAULT_COLS = 80;
+const DEFAULT_ROWS = 60;
+// Largest canvas side, in pixels, a shared grid may need; larger ones are refused.
+const MAX_CANVAS_SIDE = 8192;
 const GRID_COLOR = 'rgba(255, 255, 255, 0.07)';
 const BACKGROUND_COLOR = '#04060f';
 const CELL_COLOR = '#49ffa7';
+const ASH_COLOR = [70, 90, 140];
+// Age, in generations, from which cells are drawn fully as ash.
+const ASH_AGE = 1000;
+const HEAT_DECAY = 0.95;
+const HEAT_SCALE = 3;
+// Time per animation frame the engine may spend stepping, leaving the rest for drawing.
+const FRAME_BUDGET_MS = 8;
+// Cap on the generations per frame at unlimited speed.
+const MAX_STEPS_PER_FRAME = 1000;
+const GIF_FRAMES = 100;
+const GIF_SCALE = 6;
+const GIF_DELAY_MS = 80;
 
 const canvas = document.getElementById('game-of-life-canvas');
 const ctx = canvas.getContext('2d', { alpha: false });
-canvas.width = WORLD_COLS * CELL_SIZE;
-canvas.height = WORLD_ROWS * CELL_SIZE;
 
 const generationEl = document.getElementById('generation-count');
 const aliveCountEl = document.getElementById('alive-count');
 const nextButton = document.getElementById('next-generation');
+const playPauseButton = document.getElementById('play-pause');
+const speedSelect = document.getElementById('speed');
+const colorModeSelect = document.getElementById('color-mode');
+const downloadGifButton = document.getElementById('download-gif');
+const clearButton = document.getElementById('clear-cells');
+const shareButton = document.getElementById('share-link');
+const rleText = document.getElementById('rle-text');
+const placeRleButton = document.getElementById('place-rle');
+const exportRleButton = document.getElementById('export-rle');
+const patternHint = document.getElementById('pattern-hint');
+const DRAW_HINT = patternHint.textContent;
 
 await init();
-const lifeEngine = new LifeEngineWrapper(WORLD_COLS, WORLD_ROWS);
-lifeEngine.generate_random_square(5, 5, 50);
 
-let generation = 0;
+let lifeEngine = null;
+let worldCols = 0;
+let worldRows = 0;
+let colorMode = 'plain';
+// While drawing, the state every cell under the pointer is set to, taken from the first cell.
+let drawState = null;
+// RLE waiting to be stamped where the grid is clicked next.
+let pendingRle = null;
+let isPlaying = false;
+// Generations per second, 0 for as many as fit into the frame budget.
+let speed = Number(speedSelect.value);
+let lastFrameTime = null;
+// Generations due but not run yet, carried over between frames at slow speeds.
+let dueSteps = 0;
+useEngine(engineFromHash() ?? randomEngine());
 render();
 
+colorModeSelect.addEventListener('change', () => {
+    colorMode = colorModeSelect.value;
+    applyColorMode();
+    render();
+});
+
 nextButton.addEventListener('click', () => {
     lifeEngine.next();
-    generation += 1;
     render();
 });
 
+playPauseButton.addEventListener('click', () => {
+    isPlaying = !isPlaying;
+    playPauseButton.textContent = isPlaying ? 'Pause' : 'Play';
+    if (isPlaying) {
+        lastFrameTime = null;
+        dueSteps = 0;
+        requestAnimationFrame(animate);
+    }
+});
+
+speedSelect.addEventListener('change', () => {
+    speed = Number(speedSelect.value);
+    dueSteps = 0;
+});
+
+shareButton.addEventListener('click', async () => {
+    let state;
+    try {
+        state = lifeEngine.encode_state();
+    } catch (error) {
+        alert(`Unable to share the state: ${error}`);
+        return;
+    }
+    history.replaceState(null, '', `#${state}`);
+    try {
+        await navigator.clipboard.writeText(location.href);
+        patternHint.textContent = 'Link to this state copied to the clipboard.';
+    } catch {
+        patternHint.textContent = 'Link to this state is in the address bar.';
+    }
+});
+
+// Opening another shared link on the page, or going back to one, loads its state.
+window.addEventListener('hashchange', () => {
+    const engine = engineFromHash();
+    if (engine !== null) {
+        useEngine(engine);
+        render();
+    }
+});
+
+clearButton.addEventListener('click', () => {
+    lifeEngine.clear();
+    render();
+});
+
+canvas.addEventListener('pointerdown', (event) => {
+    const [x, y] = cellAt(event);
+    if (pendingRle !== null) {
+        try {
+            lifeEngine.load_rle(pendingRle, x, y);
+        } catch (error) {
+            alert(error);
+        }
+        setPendingRle(null);
+        render();
+        return;
+    }
+    if (!isInside(x, y)) {
+        return;
+    }
+    drawState = lifeEngine.toggle_cell(x, y);
+    canvas.setPointerCapture(event.pointerId);
+    render();
+});
+
+canvas.addEventListener('pointermove', (event) => {
+    if (drawState === null) {
+        return;
+    }
+    const [x, y] = cellAt(event);
+    if (!isInside(x, y) || lifeEngine.is_cell_alive(x, y) === drawState) {
+        return;
+    }
+    if (drawState) {
+        lifeEngine.activate_cell(x, y);
+    } else {
+        lifeEngine.deactivate_cell(x, y);
+    }
+    render();
+});
+
+canvas.addEventListener('pointerup', () => {
+    drawState = null;
+});
+
+placeRleButton.addEventListener('click', () => {
+    const text = rleText.value.trim();
+    setPendingRle(text === '' || pendingRle !== null ? null : text);
+});
+
+exportRleButton.addEventListener('click', () => {
+    rleText.value = lifeEngine.save_rle();
+    rleText.select();
+});
+
+downloadGifButton.addEventListener('click', () => {
+    let bytes;
+    try {
+        bytes = lifeEngine.export_gif(
+            GIF_FRAMES, GIF_SCALE, GIF_DELAY_MS, BACKGROUND_COLOR, CELL_COLOR, true
+        );
+    } catch (error) {
+        alert(error);
+        return;
+    }
+    const url = URL.createObjectURL(new Blob([bytes], { type: 'image/gif' }));
+    const link = document.createElement('a');
+    link.href = url;
+    link.download = `game-of-life-${lifeEngine.get_generation()}.gif`;
+    link.click();
+    URL.revokeObjectURL(url);
+});
+
 document.addEventListener('keydown', (event) => {
+    if (event.target === rleText) {
+        return;
+    }
     if (event.code === 'Space') {
         event.preventDefault();
         nextButton.click();
+    } else if (event.code === 'KeyP') {
+        playPauseButton.click();
     }
 });
 
+// The engine with the state in the URL fragment, or null if there is none or it can't be read.
+function engineFromHash() {
+    const state = location.hash.slice(1);
+    if (state === '') {
+        return null;
+    }
+    let engine;
+    try {
+        engine = LifeEngineWrapper.from_state(state);
+    } catch (error) {
+        alert(`Unable to load the shared state: ${error}`);
+        return null;
+    }
+    const maxCells = Math.floor(MAX_CANVAS_SIDE / CELL_SIZE);
+    if (engine.get_cols() > maxCells || engine.get_rows() > maxCells) {
+        alert(`Unable to load the shared state: its grid is larger than ${maxCells}×${maxCells} cells`);
+        engine.free();
+        return null;
+    }
+    return engine;
+}
+
+function randomEngine() {
+    const engine = new LifeEngineWrapper(DEFAULT_COLS, DEFAULT_ROWS);
+    engine.generate_random_square(5, 5, 50);
+    return engine;
+}
+
+// Replaces the engine, sizing the canvas to its grid and keeping the selected colours.
+function useEngine(engine) {
+    lifeEngine?.free();
+    lifeEngine = engine;
+    worldCols = engine.get_cols();
+    worldRows = engine.get_rows();
+    canvas.width = worldCols * CELL_SIZE;
+    canvas.height = worldRows * CELL_SIZE;
+    canvas.style.aspectRatio = `${worldCols} / ${worldRows}`;
+    applyColorMode();
+}
+
+function applyColorMode() {
+    lifeEngine.set_age_tracking(colorMode === 'age');
+    lifeEngine.set_heat_map(colorMode === 'heat' ? HEAT_DECAY : undefined);
+}
+
+// Runs the generations due since the last frame within the frame budget. Those that don't fit
+// are dropped rather than carried over, so a slow pattern can't snowball into catch-up work.
+function animate(time) {
+    if (!isPlaying) {
+        return;
+    }
+    const elapsed = lastFrameTime === null ? 0 : (time - lastFrameTime) / 1000;
+    lastFrameTime = time;
+    let maxSteps = MAX_STEPS_PER_FRAME;
+    if (speed > 0) {
+        dueSteps += elapsed * speed;
+        maxSteps = Math.min(Math.floor(dueSteps), MAX_STEPS_PER_FRAME);
+        dueSteps -= maxSteps;
+    }
+    if (maxSteps > 0 && lifeEngine.tick(FRAME_BUDGET_MS, maxSteps) > 0) {
+        render();
+    }
+    requestAnimationFrame(animate);
+}
+
 function render() {
     const aliveCells = drawGrid();
-    generationEl.textContent = generation.toString().padStart(3, '0');
+    generationEl.textContent = lifeEngine.get_generation().toString().padStart(3, '0');
     aliveCountEl.textContent = aliveCells.toString();
 }
 
+// The grid cell under the pointer, with the canvas scaled to fit the page.
+function cellAt(event) {
+    const rect = canvas.getBoundingClientRect();
+    return [
+        Math.floor((event.clientX - rect.left) * canvas.width / rect.width / CELL_SIZE),
+        Math.floor((event.clientY - rect.top) * canvas.height / rect.height / CELL_SIZE),
+    ];
+}
+
+function isInside(x, y) {
+    return x >= 0 && y >= 0 && x < worldCols && y < worldRows;
+}
+
+function setPendingRle(text) {
+    pendingRle = text;
+    placeRleButton.textContent = text === null ? 'Place Pattern' : 'Cancel';
+    patternHint.textContent = text === null ? DRAW_HINT : 'Click on the grid to place the top left corner of the pattern.';
+}
+
 function drawGrid() {
+    const aliveCells = lifeEngine.get_alive_cells_count();
+    if (colorMode === 'plain') {
+        // The engine rasterises the cells straight into a pixel buffer in WASM memory.
+        const pixels = lifeEngine.render_viewport(
+            0, 0, worldCols, worldRows, CELL_SIZE, BACKGROUND_COLOR, CELL_COLOR
+        );
+        ctx.putImageData(new ImageData(pixels, canvas.width, canvas.height), 0, 0);
+        drawGridLines();
+        return aliveCells;
+    }
+
     ctx.fillStyle = BACKGROUND_COLOR;
     ctx.fillRect(0, 0, canvas.width, canvas.height);
+    drawGridLines();
 
+    if (colorMode === 'age') {
+        lifeEngine.for_each_cell_with_age_do((x, y, age) => {
+            ctx.fillStyle = ageColor(age);
+            fillCell(x, y);
+        });
+    } else {
+        lifeEngine.for_each_heat_do((x, y, heat) => {
+            const t = 1 - Math.exp(-heat / HEAT_SCALE);
+            ctx.fillStyle = `rgba(255, ${Math.round(40 + 180 * t)}, 0, ${0.2 + 0.8 * t})`;
+            ctx.fillRect(x * CELL_SIZE, y * CELL_SIZE, CELL_SIZE, CELL_SIZE);
+        });
+        ctx.fillStyle = CELL_COLOR;
+        // A view into WASM memory: read it before calling into the engine again.
+        const cells = lifeEngine.alive_cells_view();
+        for (let i = 0; i < cells.length; i += 2) {
+            fillCell(cells[i], cells[i + 1]);
+        }
+    }
+    return aliveCells;
+}
+
+function drawGridLines() {
     ctx.beginPath();
     ctx.strokeStyle = GRID_COLOR;
     ctx.lineWidth = 0.5;
-    for (let x = 0; x <= WORLD_COLS; x += 1) {
+    for (let x = 0; x <= worldCols; x += 1) {
         const posX = x * CELL_SIZE + 0.5;
         ctx.moveTo(posX, 0);
         ctx.lineTo(posX, canvas.height);
     }
-    for (let y = 0; y <= WORLD_ROWS; y += 1) {
+    for (let y = 0; y <= worldRows; y += 1) {
         const posY = y * CELL_SIZE + 0.5;
         ctx.moveTo(0, posY);
         ctx.lineTo(canvas.width, posY);
     }
     ctx.stroke();
+}
 
-    ctx.fillStyle = CELL_COLOR;
-    let aliveCells = lifeEngine.get_alive_cells_count();
-    lifeEngine.for_each_cell_do((x, y) => {
-        ctx.fillRect(
-            x * CELL_SIZE + 1,
-            y * CELL_SIZE + 1,
-            CELL_SIZE - 1.5,
-            CELL_SIZE - 1.5
-        );
-    });
-    return aliveCells;
+function fillCell(x, y) {
+    ctx.fillRect(
+        x * CELL_SIZE + 1,
+        y * CELL_SIZE + 1,
+        CELL_SIZE - 1.5,
+        CELL_SIZE - 1.5
+    );
+}
+
+// Newborn cells use the accent colour and fade into ash, on a logarithmic scale, as they age.
+function ageColor(age) {
+    const t = Math.min(Math.log1p(age) / Math.log1p(ASH_AGE), 1);
+    const newborn = [73, 255, 167];
+    const [r, g, b] = newborn.map((c, i) => Math.round(c + (ASH_COLOR[i] - c) * t));
+    return `rgb(${r}, ${g}, ${b})`;
 }
@@ -1,7 +1,42 @@
-﻿use game_of_life_engine::{Cell, LifeEngine};
+﻿use game_of_life_engine::animation::{self, GifOptions};
+use game_of_life_engine::history::History;
+use game_of_life_engine::render::{Frame, Rgba};
+use game_of_life_engine::share;
+use game_of_life_engine::{Cell, LifeEngine, Region};
 use std::collections::HashSet;
 use std::fmt::Write;
 use std::io::{BufRead, Error};
+use std::path::{Path, PathBuf};
+
+const FRAME_BACKGROUND: Rgba = [255, 255, 255, 255];
+const FRAME_ALIVE: Rgba = [0, 0, 0, 255];
+
+/// Options for writing the whole grid as a numbered PNG sequence while stepping.
+pub struct FrameDump {
+    pub directory: PathBuf,
+    /// A frame is written every this many generations.
+    pub every: u64,
+    /// Size of a cell in pixels.
+    pub scale: u32,
+}
+
+impl FrameDump {
+    /// Writes a frame if the engine's generation is due, numbering frames from 0.
+    fn write(&self, engine: &LifeEngine) -> Result<(), String> {
+        let every = self.every.max(1);
+        let generation = engine.get_generation();
+        if !generation.is_multiple_of(every) {
+            return Ok(());
+        }
+        let viewport = Region::new(0, 0, engine.get_cols(), engine.get_rows());
+        let mut frame = Frame::new(viewport, self.scale, FRAME_BACKGROUND, FRAME_ALIVE)?;
+        frame.draw_engine(engine);
+        let path = self
+            .directory
+            .join(format!("frame-{:06}.png", generation / every));
+        frame.save_png(&path)
+    }
+}
 
 trait Reader {
     fn read_line(&mut self, buf: &mut String) -> Result<usize, std::io::Error>;
@@ -47,42 +82,128 @@ impl Reader for FileReader {
     }
 }
 
-pub fn run(file: Option<String>, size: u32) -> Result<(), String> {
+/// Runs the interactive session, starting from `state` if given, or else from the cells
+/// read from `file` or standard input on a `size`×`size` grid.
+pub fn run(
+    file: Option<String>,
+    size: u32,
+    state: Option<LifeEngine>,
+    frame_dump: Option<FrameDump>,
+    gif_options: GifOptions,
+) -> Result<(), String> {
     println!("Running Game of Life in console...");
-    println!("Grid size: {}x{}", size, size);
 
-    let initial_cells = match file {
-        Some(path) => {
-            println!("Reading initial cell configuration from file: {}", path);
-            read_initial_cells(size, &mut FileReader::new(path))
+    let mut engine = match state {
+        Some(engine) => {
+            println!("Grid size: {}x{}", engine.get_cols(), engine.get_rows());
+            println!(
+                "Starting from the shared state at generation {} with rule {} ({} topology)",
+                engine.get_generation(),
+                engine.get_rule(),
+                engine.get_topology()
+            );
+            engine
         }
         None => {
-            println!("Enter the initial cell configuration using the following format:");
-            println!("- Each line should contain one cell position as x,y coordinates");
-            println!("- Type 'END' on a new line when you have finished entering all cells");
-            read_initial_cells(size, &mut StdinReader::new())
+            println!("Grid size: {}x{}", size, size);
+            let initial_cells = match file {
+                Some(path) => {
+                    println!("Reading initial cell configuration from file: {}", path);
+                    read_initial_cells(size, &mut FileReader::new(path))
+                }
+                None => {
+                    println!("Enter the initial cell configuration using the following format:");
+                    println!("- Each line should contain one cell position as x,y coordinates");
+                    println!(
+                        "- Type 'END' on a new line when you have finished entering all cells"
+                    );
+                    read_initial_cells(size, &mut StdinReader::new())
+                }
+            }?;
+            LifeEngine::with_initial_cells(size, size, initial_cells)
         }
-    }?;
+    };
 
-    let mut engine = LifeEngine::with_initial_cells(size, size, initial_cells.clone());
+    let mut history = History::new();
+    if let Some(frame_dump) = &frame_dump {
+        std::fs::create_dir_all(&frame_dump.directory)
+            .map_err(|e| format!("Unable to create {}: {}", frame_dump.directory.display(), e))?;
+        frame_dump.write(&engine)?;
+    }
 
     println!("Initial alive cells: {}", engine.get_alive_cells_count());
-    println!("Press 'N' to run the next generation, 'Q' to quit");
+    println!("Press 'N' to run the next generation, 'B' to go back one generation, 'Q' to quit");
+    println!("Type 'A x,y' / 'D x,y' to activate / deactivate a cell, 'U' / 'R' to undo / redo");
+    println!("Type 'G file.gif' to save an animation of the next generations");
+    println!("Press 'S' to print the current state as a string for --state");
     let mut input = String::new();
     let stdin = std::io::stdin();
     loop {
         stdin
             .read_line(&mut input)
             .map_err(|_| "Unable to read line")?;
-        if input.trim().eq_ignore_ascii_case("N") {
+        let command = input.trim();
+        if command.eq_ignore_ascii_case("N") {
             let instant = std::time::Instant::now();
-            engine.next();
+            history.step(&mut engine);
             println!(
                 "Next generation is ready. Active cells: {}. Elapsed time: {} ms",
                 engine.get_alive_cells().count(),
                 instant.elapsed().as_millis()
             );
-        } else if input.trim().eq_ignore_ascii_case("Q") {
+            if let Some(frame_dump) = &frame_dump {
+                frame_dump.write(&engine)?;
+            }
+        } else if command.eq_ignore_ascii_case("B") {
+            if history.step_back(&mut engine) {
+                print_state("Went back", &engine);
+            } else {
+                println!("No earlier generation is kept");
+            }
+        } else if command.eq_ignore_ascii_case("U") {
+            if history.undo(&mut engine) {
+                print_state("Undone", &engine);
+            } else {
+                println!("Nothing to undo");
+            }
+        } else if command.eq_ignore_ascii_case("R") {
+            if history.redo(&mut engine) {
+                print_state("Redone", &engine);
+            } else {
+                println!("Nothing to redo");
+            }
+        } else if let Some((action, path)) = command.split_once(' ')
+            && action.eq_ignore_ascii_case("G")
+        {
+            let path = Path::new(path.trim());
+            match animation::save_gif(&engine, &gif_options, path) {
+                Ok(()) => println!(
+                    "Saved {} generations to {}",
+                    gif_options.frames.max(1),
+                    path.display()
+                ),
+                Err(e) => println!("{}", e),
+            }
+        } else if let Some((action, cell)) = command.split_once(' ') {
+            let result = parse_cell(cell).and_then(|cell| {
+                if action.eq_ignore_ascii_case("A") {
+                    history.edit(&mut engine, |e| e.activate_cell(cell.x, cell.y))
+                } else if action.eq_ignore_ascii_case("D") {
+                    history.edit(&mut engine, |e| e.deactivate_cell(cell.x, cell.y))
+                } else {
+                    Err(format!("Unknown command: {}", command))
+                }
+            });
+            match result {
+                Ok(()) => print_state("Edited", &engine),
+                Err(e) => println!("{}", e),
+            }
+        } else if command.eq_ignore_ascii_case("S") {
+            match share::encode(&engine) {
+                Ok(state) => println!("State: {}", state),
+                Err(e) => println!("Unable to share the state: {}", e),
+            }
+        } else if command.eq_ignore_ascii_case("Q") {
             break;
         }
         input.clear();
@@ -91,7 +212,32 @@ pub fn run(file: Option<String>, size: u32) -> Result<(), String> {
     Ok(())
 }
 
-const READ_CELL_ERROR: &'static str = "Invalid cell format, aborting";
+fn print_state(action: &str, engine: &LifeEngine) {
+    println!(
+        "{}. Generation: {}. Active cells: {}",
+        action,
+        engine.get_generation(),
+        engine.get_alive_cells_count()
+    );
+}
+
+fn parse_cell(input: &str) -> Result<Cell, String> {
+    let split = input.trim().split(',').collect::<Vec<&str>>();
+    if split.len() != 2 {
+        return Err(READ_CELL_ERROR.to_string());
+    }
+    let x = split[0]
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| READ_CELL_ERROR)?;
+    let y = split[1]
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| READ_CELL_ERROR)?;
+    Ok(Cell::new(x, y))
+}
+
+const READ_CELL_ERROR: &str = "Invalid cell format";
 fn read_initial_cells(size: u32, reader: &mut impl Reader) -> Result<HashSet<Cell>, String> {
     let mut cells = HashSet::new();
 
@@ -105,12 +251,7 @@ fn read_initial_cells(size: u32, reader: &mut impl Reader) -> Result<HashSet<Cel
             break;
         }
 
-        let split = input.trim().split(',').collect::<Vec<&str>>();
-        if split.len() != 2 {
-            return Err(READ_CELL_ERROR.to_string());
-        }
-        let x = split[0].parse::<u32>().map_err(|_| READ_CELL_ERROR)?;
-        let y = split[1].parse::<u32>().map_err(|_| READ_CELL_ERROR)?;
+        let Cell { x, y } = parse_cell(&input).map_err(|e| format!("{}, aborting", e))?;
 
         if x >= size || y >= size {
             return Err(format!("Invalid cell position: ({}, {}), aborting", x, y));
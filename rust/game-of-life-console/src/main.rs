@@ -1,5 +1,10 @@
 use clap::Parser;
-use game_of_life_console::run;
+use game_of_life_console::{FrameDump, run};
+use game_of_life_engine::Region;
+use game_of_life_engine::animation::{GifCrop, GifOptions};
+use game_of_life_engine::render::{Rgba, parse_color};
+use game_of_life_engine::share;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command()]
@@ -11,14 +16,87 @@ pub struct Args {
     /// Size of the square grid
     #[arg(short, long, default_value = "1000")]
     size: u32,
+
+    /// State printed by the 'S' command or shared from the web page to start from, with its own grid size, rule and generation. Grids are at most 65536 cells wide and tall
+    #[arg(long, conflicts_with = "file")]
+    state: Option<String>,
+
+    /// Directory to write the grid to as a numbered PNG sequence (frame-000000.png, ...) while stepping
+    #[arg(long)]
+    png_dir: Option<PathBuf>,
+
+    /// Write a PNG frame every this many generations
+    #[arg(long, default_value = "1")]
+    png_every: u64,
+
+    /// Size of a cell in the PNG frames, in pixels
+    #[arg(long, default_value = "1")]
+    png_scale: u32,
+
+    /// Number of frames of the animated GIFs written with the 'G' command
+    #[arg(long, default_value = "50")]
+    gif_frames: u32,
+
+    /// Size of a cell in the animated GIFs, in pixels
+    #[arg(long, default_value = "4")]
+    gif_scale: u32,
+
+    /// Time each frame of the animated GIFs is shown for, in milliseconds
+    #[arg(long, default_value = "100")]
+    gif_delay: u32,
+
+    /// Colour of dead cells in the animated GIFs, as #rrggbb or #rrggbbaa (#00000000 for transparent)
+    #[arg(long, default_value = "#ffffff", value_parser = parse_color)]
+    gif_background: Rgba,
+
+    /// Colour of alive cells in the animated GIFs, as #rrggbb
+    #[arg(long, default_value = "#000000", value_parser = parse_color)]
+    gif_alive: Rgba,
+
+    /// Cells kept around the pattern when cropping the animated GIFs to it
+    #[arg(long, default_value = "2")]
+    gif_margin: u32,
+
+    /// Show the whole grid in the animated GIFs instead of cropping them to the pattern
+    #[arg(long)]
+    gif_whole_grid: bool,
 }
 
 fn main() {
     let args = Args::parse();
-    match run(args.file, args.size) {
+    let state = match args.state.as_deref().map(share::decode).transpose() {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let (cols, rows) = state
+        .as_ref()
+        .map_or((args.size, args.size), |e| (e.get_cols(), e.get_rows()));
+    let frame_dump = args.png_dir.map(|directory| FrameDump {
+        directory,
+        every: args.png_every,
+        scale: args.png_scale,
+    });
+    let gif_options = GifOptions {
+        frames: args.gif_frames,
+        scale: args.gif_scale,
+        frame_delay_ms: args.gif_delay,
+        background: args.gif_background,
+        alive: args.gif_alive,
+        crop: if args.gif_whole_grid {
+            GifCrop::Region(Region::new(0, 0, cols, rows))
+        } else {
+            GifCrop::BoundingBox {
+                margin: args.gif_margin,
+            }
+        },
+    };
+    match run(args.file, args.size, state, frame_dump, gif_options) {
         Ok(_) => {}
         Err(e) => {
-            println!("{}", e);
+            eprintln!("{}", e);
             std::process::exit(1);
         }
     }
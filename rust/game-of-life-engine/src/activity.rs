@@ -0,0 +1,115 @@
+use crate::Cell;
+use rustc_hash::FxHashMap;
+
+/// Heat below which a cell counts as cold and is eventually forgotten.
+const MIN_HEAT: f32 = 0.01;
+/// How many steps apart cold cells are removed from the map.
+const PRUNE_INTERVAL: u64 = 64;
+
+/// Decaying measure of how often each cell has changed state lately. Every birth or
+/// death adds 1 to the heat of its cell, and all heat is multiplied by `decay` each step,
+/// so busy regions stay hot while stable ones cool down.
+///
+/// Decay is applied lazily when a cell is read or changes again, which keeps each step
+/// proportional to the number of changed cells.
+#[derive(PartialEq, Debug, Clone)]
+pub struct HeatMap {
+    decay: f32,
+    step: u64,
+    /// Heat of each cell as of the step it was last updated.
+    cells: FxHashMap<Cell, (f32, u64)>,
+}
+
+// The decay is checked to be a number, and heat is only ever built from additions and
+// multiplications of values in [0, 1] and counts, so it is never NaN.
+impl Eq for HeatMap {}
+
+impl HeatMap {
+    /// Creates an empty heat map. `decay` is clamped to the [0, 1] range, and rejected if NaN.
+    pub fn new(decay: f32) -> Result<HeatMap, String> {
+        if decay.is_nan() {
+            return Err("The heat map decay must be a number".to_string());
+        }
+        Ok(HeatMap {
+            decay: decay.clamp(0.0, 1.0),
+            step: 0,
+            cells: FxHashMap::default(),
+        })
+    }
+
+    pub fn get_decay(&self) -> f32 {
+        self.decay
+    }
+
+    /// Current heat of a cell, 0 for cells that have not changed lately.
+    pub fn get(&self, cell: &Cell) -> f32 {
+        self.cells
+            .get(cell)
+            .map_or(0.0, |(heat, step)| self.decayed(*heat, *step))
+    }
+
+    /// Iterates the cells that are still warm, with their current heat.
+    pub fn iter(&self) -> impl Iterator<Item = (&Cell, f32)> + '_ {
+        self.cells
+            .iter()
+            .map(|(cell, (heat, step))| (cell, self.decayed(*heat, *step)))
+            .filter(|(_, heat)| *heat >= MIN_HEAT)
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    /// Advances by one step, heating up the cells that changed during it.
+    pub(crate) fn record(&mut self, births: &[Cell], deaths: &[Cell]) {
+        self.step += 1;
+        for cell in births.iter().chain(deaths.iter()) {
+            let heat = self.get(cell) + 1.0;
+            self.cells.insert(cell.clone(), (heat, self.step));
+        }
+        if self.step.is_multiple_of(PRUNE_INTERVAL) {
+            let (decay, now) = (self.decay, self.step);
+            self.cells
+                .retain(|_, (heat, step)| decayed(decay, *heat, now - *step) >= MIN_HEAT);
+        }
+    }
+
+    fn decayed(&self, heat: f32, step: u64) -> f32 {
+        decayed(self.decay, heat, self.step - step)
+    }
+}
+
+fn decayed(decay: f32, heat: f32, elapsed_steps: u64) -> f32 {
+    heat * decay.powi(elapsed_steps.min(i32::MAX as u64) as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decay_is_clamped() {
+        assert_eq!(HeatMap::new(1.5).unwrap().get_decay(), 1.0);
+        assert_eq!(HeatMap::new(-0.5).unwrap().get_decay(), 0.0);
+        assert_eq!(HeatMap::new(f32::INFINITY).unwrap().get_decay(), 1.0);
+        assert_eq!(HeatMap::new(f32::NEG_INFINITY).unwrap().get_decay(), 0.0);
+    }
+
+    #[test]
+    fn nan_decay_is_rejected() {
+        assert!(HeatMap::new(f32::NAN).is_err());
+    }
+
+    #[test]
+    fn heat_decays_between_changes() {
+        let cell = Cell::new(1, 2);
+        let mut heat_map = HeatMap::new(0.5).unwrap();
+        heat_map.record(std::slice::from_ref(&cell), &[]);
+        assert_eq!(heat_map.get(&cell), 1.0);
+        heat_map.record(&[], &[]);
+        assert_eq!(heat_map.get(&cell), 0.5);
+        heat_map.record(&[], std::slice::from_ref(&cell));
+        assert_eq!(heat_map.get(&cell), 1.25);
+        assert_eq!(heat_map, heat_map.clone());
+    }
+}
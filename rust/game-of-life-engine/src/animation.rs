@@ -0,0 +1,234 @@
+use crate::render::{Frame, Rgba};
+use crate::{Cell, LifeEngine, Region};
+use gif::{DisposalMethod, Encoder, Repeat};
+use std::borrow::Cow;
+use std::cmp::{max, min};
+use std::io::Write;
+use std::path::Path;
+
+/// Largest number of pixels in a frame, which keeps its RGBA buffer within 256 MiB.
+const MAX_PIXELS: u64 = 1 << 26;
+
+/// Part of the world an animation shows.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum GifCrop {
+    /// A fixed region of the world.
+    Region(Region),
+    /// The smallest region containing every alive cell of every frame, grown by `margin`
+    /// cells on each side and cut at the edges of the world.
+    BoundingBox { margin: u32 },
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct GifOptions {
+    /// Number of frames, the first being the current generation.
+    pub frames: u32,
+    /// Size of a cell in pixels.
+    pub scale: u32,
+    /// Time each frame is shown for. GIF delays are counted in hundredths of a second,
+    /// so this is rounded down to a multiple of 10 ms.
+    pub frame_delay_ms: u32,
+    /// Colour of dead cells. A fully transparent colour makes the animation transparent.
+    pub background: Rgba,
+    pub alive: Rgba,
+    pub crop: GifCrop,
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        GifOptions {
+            frames: 50,
+            scale: 4,
+            frame_delay_ms: 100,
+            background: [255, 255, 255, 255],
+            alive: [0, 0, 0, 255],
+            crop: GifCrop::BoundingBox { margin: 2 },
+        }
+    }
+}
+
+/// Encodes an animated GIF of the engine's next `options.frames` generations, starting
+/// with the current one. The engine itself is left untouched: the run happens on a copy.
+pub fn write_gif(
+    engine: &LifeEngine,
+    options: &GifOptions,
+    writer: impl Write,
+) -> Result<(), String> {
+    let mut engine = engine.clone();
+    engine.set_age_tracking(false);
+    engine.set_heat_map(None)?;
+    let frames = options.frames.max(1);
+    let viewport = match options.crop {
+        GifCrop::Region(region) => region,
+        GifCrop::BoundingBox { margin } => run_bounding_box(&engine, frames, margin)
+            .ok_or("There are no alive cells to crop the animation to")?,
+    };
+
+    // Checked before the frame is allocated, since its buffer grows with the square of the scale.
+    let (width, height) = Frame::pixel_size(&viewport, options.scale)?;
+    let (Ok(width), Ok(height)) = (u16::try_from(width), u16::try_from(height)) else {
+        return Err(format!(
+            "The animation would be {}×{} pixels, GIF images are at most {}×{}",
+            width,
+            height,
+            u16::MAX,
+            u16::MAX
+        ));
+    };
+    if width == 0 || height == 0 {
+        return Err("The animation would be empty".to_string());
+    }
+    if width as u64 * height as u64 > MAX_PIXELS {
+        return Err(format!(
+            "The animation would be {}×{} pixels, frames have at most {} pixels",
+            width, height, MAX_PIXELS
+        ));
+    }
+    let mut frame = Frame::new(viewport, options.scale, options.background, options.alive)?;
+
+    let [background_r, background_g, background_b, background_a] = options.background;
+    let [alive_r, alive_g, alive_b, _] = options.alive;
+    let palette = [
+        background_r,
+        background_g,
+        background_b,
+        alive_r,
+        alive_g,
+        alive_b,
+    ];
+    let is_transparent = background_a == 0;
+    let delay = (options.frame_delay_ms / 10).min(u16::MAX as u32) as u16;
+
+    let mut encoder = Encoder::new(writer, width, height, &palette).map_err(|e| e.to_string())?;
+    encoder
+        .set_repeat(Repeat::Infinite)
+        .map_err(|e| e.to_string())?;
+    frame.draw_engine(&engine);
+    for index in 0..frames {
+        if index > 0 {
+            engine.next();
+            frame.apply_last_step(&engine);
+        }
+        // The frame only ever holds the two palette colours, so every pixel maps to an index.
+        let indices: Vec<u8> = frame
+            .pixels()
+            .chunks_exact(4)
+            .map(|pixel| u8::from(pixel == options.alive))
+            .collect();
+        let gif_frame = gif::Frame {
+            width,
+            height,
+            delay,
+            // Transparent frames would otherwise show the cells of the previous ones through.
+            dispose: if is_transparent {
+                DisposalMethod::Background
+            } else {
+                DisposalMethod::Keep
+            },
+            transparent: is_transparent.then_some(0),
+            buffer: Cow::Owned(indices),
+            ..gif::Frame::default()
+        };
+        encoder.write_frame(&gif_frame).map_err(|e| e.to_string())?;
+    }
+    encoder.into_inner().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Writes an animated GIF of the engine's next generations to a file.
+pub fn save_gif(engine: &LifeEngine, options: &GifOptions, path: &Path) -> Result<(), String> {
+    let file = std::fs::File::create(path)
+        .map_err(|e| format!("Unable to create {}: {}", path.display(), e))?;
+    write_gif(engine, options, std::io::BufWriter::new(file))
+        .map_err(|e| format!("Unable to write {}: {}", path.display(), e))
+}
+
+/// Returns the region covering the alive cells of the next `frames` generations,
+/// grown by `margin` cells and cut at the edges of the world.
+fn run_bounding_box(engine: &LifeEngine, frames: u32, margin: u32) -> Option<Region> {
+    let mut engine = engine.clone();
+    let mut bounds: Option<(Cell, Cell)> = None;
+    for index in 0..frames {
+        if index > 0 {
+            engine.next();
+        }
+        if let Some((top_left, bottom_right)) = engine.get_bounding_box() {
+            bounds = Some(match bounds {
+                Some((a, b)) => (
+                    Cell::new(min(a.x, top_left.x), min(a.y, top_left.y)),
+                    Cell::new(max(b.x, bottom_right.x), max(b.y, bottom_right.y)),
+                ),
+                None => (top_left, bottom_right),
+            });
+        }
+    }
+    let (top_left, bottom_right) = bounds?;
+    let last_col = engine.get_cols().saturating_sub(1);
+    let last_row = engine.get_rows().saturating_sub(1);
+    Some(Region::from_corners(
+        &Cell::new(
+            top_left.x.saturating_sub(margin),
+            top_left.y.saturating_sub(margin),
+        ),
+        &Cell::new(
+            min(bottom_right.x.saturating_add(margin), last_col),
+            min(bottom_right.y.saturating_add(margin), last_row),
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glider() -> LifeEngine {
+        let mut engine = LifeEngine::new(1000, 1000);
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            engine.activate_cell(x, y).unwrap();
+        }
+        engine
+    }
+
+    fn whole_grid(scale: u32) -> GifOptions {
+        GifOptions {
+            frames: 2,
+            scale,
+            crop: GifCrop::Region(Region::new(0, 0, 1000, 1000)),
+            ..GifOptions::default()
+        }
+    }
+
+    #[test]
+    fn oversized_animations_are_rejected_before_drawing() {
+        // 60000×60000 pixels fit into a GIF but not into the pixel budget.
+        let error = write_gif(&glider(), &whole_grid(60), Vec::new()).unwrap_err();
+        assert!(error.contains("at most"), "{}", error);
+        let error = write_gif(&glider(), &whole_grid(70), Vec::new()).unwrap_err();
+        assert!(error.contains("GIF images"), "{}", error);
+        let error = write_gif(&glider(), &whole_grid(u32::MAX), Vec::new()).unwrap_err();
+        assert!(error.contains("too large"), "{}", error);
+    }
+
+    #[test]
+    fn animation_is_cropped_to_the_pattern() {
+        let options = GifOptions {
+            frames: 8,
+            scale: 2,
+            ..GifOptions::default()
+        };
+        let mut bytes = Vec::new();
+        write_gif(&glider(), &options, &mut bytes).unwrap();
+
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(bytes.as_slice())
+            .unwrap();
+        // The glider moves by 2 cells in 8 generations: 5×5 cells, plus a margin of 2 cells
+        // cut at the top and left edges of the world.
+        assert_eq!((decoder.width(), decoder.height()), (14, 14));
+        let mut frames = 0;
+        while decoder.read_next_frame().unwrap().is_some() {
+            frames += 1;
+        }
+        assert_eq!(frames, 8);
+    }
+}
@@ -0,0 +1,225 @@
+use crate::pattern::Pattern;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum PatternCategory {
+    StillLife,
+    Oscillator,
+    Spaceship,
+    Gun,
+    Methuselah,
+}
+
+impl PatternCategory {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PatternCategory::StillLife => "Still lifes",
+            PatternCategory::Oscillator => "Oscillators",
+            PatternCategory::Spaceship => "Spaceships",
+            PatternCategory::Gun => "Guns",
+            PatternCategory::Methuselah => "Methuselahs",
+        }
+    }
+}
+
+/// A named, well known pattern from the built-in catalogue.
+#[derive(Debug)]
+pub struct CatalogueEntry {
+    pub name: &'static str,
+    pub category: PatternCategory,
+    plaintext: &'static str,
+}
+
+impl CatalogueEntry {
+    pub fn pattern(&self) -> Pattern {
+        Pattern::from_plaintext(self.plaintext).expect("catalogue patterns are valid plaintext")
+    }
+}
+
+pub fn find(name: &str) -> Option<&'static CatalogueEntry> {
+    CATALOGUE
+        .iter()
+        .find(|entry| entry.name.eq_ignore_ascii_case(name))
+}
+
+pub static CATALOGUE: &[CatalogueEntry] = &[
+    CatalogueEntry {
+        name: "Block",
+        category: PatternCategory::StillLife,
+        plaintext: "\
+            OO\n\
+            OO",
+    },
+    CatalogueEntry {
+        name: "Beehive",
+        category: PatternCategory::StillLife,
+        plaintext: "\
+            .OO.\n\
+            O..O\n\
+            .OO.",
+    },
+    CatalogueEntry {
+        name: "Loaf",
+        category: PatternCategory::StillLife,
+        plaintext: "\
+            .OO.\n\
+            O..O\n\
+            .O.O\n\
+            ..O.",
+    },
+    CatalogueEntry {
+        name: "Boat",
+        category: PatternCategory::StillLife,
+        plaintext: "\
+            OO.\n\
+            O.O\n\
+            .O.",
+    },
+    CatalogueEntry {
+        name: "Blinker",
+        category: PatternCategory::Oscillator,
+        plaintext: "OOO",
+    },
+    CatalogueEntry {
+        name: "Toad",
+        category: PatternCategory::Oscillator,
+        plaintext: "\
+            .OOO\n\
+            OOO.",
+    },
+    CatalogueEntry {
+        name: "Beacon",
+        category: PatternCategory::Oscillator,
+        plaintext: "\
+            OO..\n\
+            OO..\n\
+            ..OO\n\
+            ..OO",
+    },
+    CatalogueEntry {
+        name: "Pulsar",
+        category: PatternCategory::Oscillator,
+        plaintext: "\
+            ..OOO...OOO..\n\
+            .............\n\
+            O....O.O....O\n\
+            O....O.O....O\n\
+            O....O.O....O\n\
+            ..OOO...OOO..\n\
+            .............\n\
+            ..OOO...OOO..\n\
+            O....O.O....O\n\
+            O....O.O....O\n\
+            O....O.O....O\n\
+            .............\n\
+            ..OOO...OOO..",
+    },
+    CatalogueEntry {
+        name: "Pentadecathlon",
+        category: PatternCategory::Oscillator,
+        plaintext: "\
+            ..O....O..\n\
+            OO.OOOO.OO\n\
+            ..O....O..",
+    },
+    CatalogueEntry {
+        name: "Glider",
+        category: PatternCategory::Spaceship,
+        plaintext: "\
+            .O.\n\
+            ..O\n\
+            OOO",
+    },
+    CatalogueEntry {
+        name: "LWSS",
+        category: PatternCategory::Spaceship,
+        plaintext: "\
+            .O..O\n\
+            O....\n\
+            O...O\n\
+            OOOO.",
+    },
+    CatalogueEntry {
+        name: "MWSS",
+        category: PatternCategory::Spaceship,
+        plaintext: "\
+            ...O..\n\
+            .O...O\n\
+            O.....\n\
+            O....O\n\
+            OOOOO.",
+    },
+    CatalogueEntry {
+        name: "HWSS",
+        category: PatternCategory::Spaceship,
+        plaintext: "\
+            ...OO..\n\
+            .O....O\n\
+            O......\n\
+            O.....O\n\
+            OOOOOO.",
+    },
+    CatalogueEntry {
+        name: "Gosper glider gun",
+        category: PatternCategory::Gun,
+        plaintext: "\
+            ........................O...........\n\
+            ......................O.O...........\n\
+            ............OO......OO............OO\n\
+            ...........O...O....OO............OO\n\
+            OO........O.....O...OO..............\n\
+            OO........O...O.OO....O.O...........\n\
+            ..........O.....O.......O...........\n\
+            ...........O...O....................\n\
+            ............OO......................",
+    },
+    CatalogueEntry {
+        name: "Simkin glider gun",
+        category: PatternCategory::Gun,
+        plaintext: "\
+            OO.....OO........................\n\
+            OO.....OO........................\n\
+            .................................\n\
+            ....OO...........................\n\
+            ....OO...........................\n\
+            .................................\n\
+            .................................\n\
+            .................................\n\
+            .................................\n\
+            ......................OO.OO......\n\
+            .....................O.....O.....\n\
+            .....................O......O..OO\n\
+            .....................OOO...O...OO\n\
+            ..........................O......\n\
+            .................................\n\
+            .................................\n\
+            .................................\n\
+            ....................OO...........\n\
+            ....................O............\n\
+            .....................OOO.........\n\
+            .......................O.........",
+    },
+    CatalogueEntry {
+        name: "R-pentomino",
+        category: PatternCategory::Methuselah,
+        plaintext: "\
+            .OO\n\
+            OO.\n\
+            .O.",
+    },
+    CatalogueEntry {
+        name: "Diehard",
+        category: PatternCategory::Methuselah,
+        plaintext: "\
+            ......O.\n\
+            OO......\n\
+            .O...OOO",
+    },
+    CatalogueEntry {
+        name: "Acorn",
+        category: PatternCategory::Methuselah,
+        plaintext: "\
+            .O.....\n\
+            ...O...\n\
+            OO..OOO",
+    },
+];
@@ -0,0 +1,437 @@
+use crate::{Cell, Pattern, Rule};
+use std::path::Path;
+
+const RLE_LINE_LENGTH: usize = 70;
+
+/// Largest width or height of a parsed RLE pattern, in cells. Runs are only a few digits
+/// long, so without a limit a short text could describe an enormous pattern.
+pub const MAX_PATTERN_SIDE: u32 = 1 << 16;
+/// Largest number of alive cells in a parsed RLE pattern.
+pub const MAX_PATTERN_CELLS: usize = 1 << 24;
+
+/// Text formats patterns can be read from and written to.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum PatternFormat {
+    /// Run length encoded, as used by Golly and LifeWiki (`.rle`).
+    Rle,
+    /// Plaintext rows of `.` and `O` (`.cells`).
+    Plaintext,
+    /// One `x,y` coordinate pair per line, as read by the console app.
+    Coordinates,
+}
+
+/// A pattern together with the metadata that pattern files can carry.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct PatternDocument {
+    pub pattern: Pattern,
+    /// Where the pattern's top left corner is placed in the grid, if the file specifies it.
+    pub origin: Option<Cell>,
+    pub rule: Option<Rule>,
+    pub name: Option<String>,
+}
+
+impl PatternDocument {
+    pub fn new(pattern: Pattern) -> PatternDocument {
+        PatternDocument {
+            pattern,
+            origin: None,
+            rule: None,
+            name: None,
+        }
+    }
+}
+
+impl PatternFormat {
+    pub fn from_extension(path: &Path) -> Option<PatternFormat> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "rle" => Some(PatternFormat::Rle),
+            "cells" => Some(PatternFormat::Plaintext),
+            "txt" | "csv" => Some(PatternFormat::Coordinates),
+            _ => None,
+        }
+    }
+
+    /// Guesses the format from the first meaningful line of `text`.
+    pub fn detect(text: &str) -> PatternFormat {
+        let first_line = text
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .unwrap_or("");
+        if text.trim_start().starts_with('#') || first_line.starts_with('x') {
+            PatternFormat::Rle
+        } else if first_line.contains(',') {
+            PatternFormat::Coordinates
+        } else {
+            PatternFormat::Plaintext
+        }
+    }
+
+    /// Picks the format from the file extension, falling back to detecting it from the contents.
+    pub fn for_file(path: &Path, text: &str) -> PatternFormat {
+        Self::from_extension(path).unwrap_or_else(|| Self::detect(text))
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PatternFormat::Rle => "rle",
+            PatternFormat::Plaintext => "cells",
+            PatternFormat::Coordinates => "txt",
+        }
+    }
+
+    pub fn parse(&self, text: &str) -> Result<PatternDocument, String> {
+        match self {
+            PatternFormat::Rle => parse_rle(text),
+            PatternFormat::Plaintext => parse_plaintext(text),
+            PatternFormat::Coordinates => parse_coordinates(text),
+        }
+    }
+
+    pub fn write(&self, document: &PatternDocument) -> String {
+        match self {
+            PatternFormat::Rle => write_rle(document),
+            PatternFormat::Plaintext => write_plaintext(document),
+            PatternFormat::Coordinates => write_coordinates(document),
+        }
+    }
+}
+
+/// Parses RLE. `#N` gives the name, `#R`/`#P` the position of the top left corner
+/// and the header line may carry a `rule`; other `#` lines are ignored.
+///
+/// Golly writes positions relative to the middle of its unbounded universe, so they are
+/// often negative. A grid starts at 0, so such positions are dropped and the pattern is
+/// placed as if the file had none, which centres it like Golly does.
+///
+/// Patterns wider or taller than `MAX_PATTERN_SIDE` or with more than `MAX_PATTERN_CELLS`
+/// alive cells are rejected.
+pub fn parse_rle(text: &str) -> Result<PatternDocument, String> {
+    let mut document = PatternDocument::new(Pattern::new(Vec::new()));
+    let mut cells = Vec::new();
+    let mut x = 0u32;
+    let mut y = 0u32;
+    let mut run_count: Option<u32> = None;
+    let mut header_seen = false;
+
+    'lines: for line in text.lines().map(str::trim) {
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut chars = comment.chars();
+            let kind = chars.next();
+            let value = chars.as_str();
+            match kind {
+                Some('N') => document.name = Some(value.trim().to_string()),
+                Some('R') | Some('P') => document.origin = parse_origin(value)?,
+                _ => {}
+            }
+            continue;
+        }
+        if !header_seen && line.starts_with('x') {
+            header_seen = true;
+            document.rule = parse_rle_header(line)?;
+            continue;
+        }
+        for symbol in line.chars() {
+            match symbol {
+                '0'..='9' => {
+                    let digit = symbol.to_digit(10).unwrap_or(0);
+                    let count = run_count
+                        .unwrap_or(0)
+                        .checked_mul(10)
+                        .and_then(|count| count.checked_add(digit))
+                        .ok_or("run count too large in RLE pattern")?;
+                    run_count = Some(count);
+                }
+                'b' | '.' => x = advance(x, run_count.take(), "wider")?,
+                '$' => {
+                    // y is the row of the cells that follow, so it stays below the limit.
+                    y = advance(y.saturating_add(1), run_count.take(), "taller")? - 1;
+                    x = 0;
+                }
+                '!' => break 'lines,
+                c if c.is_ascii_alphabetic() => {
+                    let start = x;
+                    x = advance(x, run_count.take(), "wider")?;
+                    if cells.len() + (x - start) as usize > MAX_PATTERN_CELLS {
+                        return Err(format!(
+                            "RLE pattern has more than {} alive cells",
+                            MAX_PATTERN_CELLS
+                        ));
+                    }
+                    cells.extend((start..x).map(|x| Cell::new(x, y)));
+                }
+                c if c.is_whitespace() => {}
+                c => return Err(format!("unexpected symbol '{}' in RLE pattern", c)),
+            }
+        }
+    }
+
+    document.pattern = Pattern::new(cells);
+    Ok(document)
+}
+
+/// Moves an RLE position by a run, 1 if it has no count, staying within `MAX_PATTERN_SIDE`.
+fn advance(position: u32, run_count: Option<u32>, direction: &str) -> Result<u32, String> {
+    position
+        .checked_add(run_count.unwrap_or(1))
+        .filter(|position| *position <= MAX_PATTERN_SIDE)
+        .ok_or_else(|| {
+            format!(
+                "RLE pattern is {} than {} cells",
+                direction, MAX_PATTERN_SIDE
+            )
+        })
+}
+
+/// Parses the `x y` of a `#R`/`#P` line, returning `None` for negative positions.
+fn parse_origin(value: &str) -> Result<Option<Cell>, String> {
+    let invalid = || format!("invalid pattern position '{}'", value.trim());
+    let coordinates = value
+        .split_whitespace()
+        .map(|v| v.parse::<i64>())
+        .collect::<Result<Vec<i64>, _>>()
+        .map_err(|_| invalid())?;
+    let [x, y] = coordinates[..] else {
+        return Err(invalid());
+    };
+    if x < 0 || y < 0 {
+        return Ok(None);
+    }
+    match (u32::try_from(x), u32::try_from(y)) {
+        (Ok(x), Ok(y)) => Ok(Some(Cell::new(x, y))),
+        _ => Err(invalid()),
+    }
+}
+
+fn parse_rle_header(line: &str) -> Result<Option<Rule>, String> {
+    for entry in line.split(',') {
+        if let Some((key, value)) = entry.split_once('=')
+            && key.trim() == "rule"
+        {
+            return value.trim().parse::<Rule>().map(Some);
+        }
+    }
+    Ok(None)
+}
+
+pub fn write_rle(document: &PatternDocument) -> String {
+    let pattern = &document.pattern;
+    let mut text = String::new();
+    if let Some(name) = &document.name {
+        text.push_str(&format!("#N {}\n", name));
+    }
+    if let Some(origin) = &document.origin {
+        text.push_str(&format!("#R {} {}\n", origin.x, origin.y));
+    }
+    text.push_str(&format!(
+        "x = {}, y = {}, rule = {}\n",
+        pattern.width(),
+        pattern.height(),
+        document.rule.unwrap_or_default()
+    ));
+
+    // Pattern cells are sorted row by row, so runs can be emitted in a single pass.
+    let mut runs: Vec<(u32, char)> = Vec::new();
+    let mut push_run = |count: u32, symbol: char| {
+        if count == 0 {
+            return;
+        }
+        match runs.last_mut() {
+            Some((last_count, last_symbol)) if *last_symbol == symbol => *last_count += count,
+            _ => runs.push((count, symbol)),
+        }
+    };
+    let mut x = 0;
+    let mut y = 0;
+    for cell in pattern.cells() {
+        if cell.y != y {
+            push_run(cell.y - y, '$');
+            y = cell.y;
+            x = 0;
+        }
+        push_run(cell.x - x, 'b');
+        push_run(1, 'o');
+        x = cell.x + 1;
+    }
+    push_run(1, '!');
+
+    let mut line = String::new();
+    for (count, symbol) in runs {
+        let run = if count == 1 {
+            symbol.to_string()
+        } else {
+            format!("{}{}", count, symbol)
+        };
+        if line.len() + run.len() > RLE_LINE_LENGTH {
+            text.push_str(&line);
+            text.push('\n');
+            line.clear();
+        }
+        line.push_str(&run);
+    }
+    text.push_str(&line);
+    text.push('\n');
+    text
+}
+
+pub fn parse_plaintext(text: &str) -> Result<PatternDocument, String> {
+    let mut document = PatternDocument::new(Pattern::from_plaintext(text)?);
+    document.name = text
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("!Name:"))
+        .map(|name| name.trim().to_string());
+    Ok(document)
+}
+
+pub fn write_plaintext(document: &PatternDocument) -> String {
+    let pattern = &document.pattern;
+    let mut text = String::new();
+    if let Some(name) = &document.name {
+        text.push_str(&format!("!Name: {}\n", name));
+    }
+    let mut cells = pattern.cells().iter().peekable();
+    for y in 0..pattern.height() {
+        let mut row = String::new();
+        while let Some(cell) = cells.next_if(|c| c.y == y) {
+            row.extend(std::iter::repeat_n('.', (cell.x as usize) - row.len()));
+            row.push('O');
+        }
+        text.push_str(&row);
+        text.push('\n');
+    }
+    text
+}
+
+/// Parses `x,y` lines. The coordinates are absolute, so the document's origin is always set.
+pub fn parse_coordinates(text: &str) -> Result<PatternDocument, String> {
+    let mut cells = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (x, y) = line
+            .split_once(',')
+            .ok_or_else(|| format!("invalid cell '{}', expected x,y", line))?;
+        let x = x.trim().parse::<u32>();
+        let y = y.trim().parse::<u32>();
+        match (x, y) {
+            (Ok(x), Ok(y)) => cells.push(Cell::new(x, y)),
+            _ => return Err(format!("invalid cell '{}', expected x,y", line)),
+        }
+    }
+    let origin = Cell::new(
+        cells.iter().map(|c| c.x).min().unwrap_or(0),
+        cells.iter().map(|c| c.y).min().unwrap_or(0),
+    );
+    let mut document = PatternDocument::new(Pattern::new(cells));
+    document.origin = Some(origin);
+    Ok(document)
+}
+
+pub fn write_coordinates(document: &PatternDocument) -> String {
+    let origin = document.origin.clone().unwrap_or(Cell::new(0, 0));
+    document
+        .pattern
+        .cells_at(&origin)
+        .map(|c| format!("{},{}\n", c.x, c.y))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oversized_rle_runs_are_rejected() {
+        for text in [
+            "x = 1, y = 1\n4294967295b2bo!",
+            "4294967295$2$o!",
+            "999999999o!",
+            "65536bo!",
+            "65536$o!",
+        ] {
+            assert!(parse_rle(text).is_err(), "{}", text);
+        }
+        let error = parse_rle(&"65536o$".repeat(257)).unwrap_err();
+        assert!(error.contains("alive cells"), "{}", error);
+    }
+
+    #[test]
+    fn rle_runs_up_to_the_limits_are_read() {
+        let document = parse_rle("65535bo$65534$o!").unwrap();
+        assert_eq!(document.pattern.width(), MAX_PATTERN_SIDE);
+        assert_eq!(document.pattern.height(), MAX_PATTERN_SIDE);
+        assert_eq!(document.pattern.get_alive_cells_count(), 2);
+    }
+
+    #[test]
+    fn rle_positions_are_read() {
+        let document = parse_rle("#N Glider\n#R 10 20\nx = 3, y = 3\nbo$2bo$3o!").unwrap();
+        assert_eq!(document.name.as_deref(), Some("Glider"));
+        assert_eq!(document.origin, Some(Cell::new(10, 20)));
+        assert_eq!(document.pattern.get_alive_cells_count(), 5);
+    }
+
+    #[test]
+    fn negative_rle_positions_are_dropped() {
+        for line in ["#R -1 -1", "#P -12 4", "#R 3 -8"] {
+            let text = format!("{}\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!", line);
+            let document = parse_rle(&text).unwrap();
+            assert_eq!(document.origin, None, "{}", line);
+            assert_eq!(document.pattern.get_alive_cells_count(), 5, "{}", line);
+        }
+    }
+
+    #[test]
+    fn invalid_rle_positions_are_rejected() {
+        for line in ["#R 1", "#R 1 2 3", "#R a b", "#R 1 99999999999"] {
+            let text = format!("{}\nx = 1, y = 1\no!", line);
+            assert!(parse_rle(&text).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn written_extensions_are_read_back() {
+        for format in [
+            PatternFormat::Rle,
+            PatternFormat::Plaintext,
+            PatternFormat::Coordinates,
+        ] {
+            let path = format!("pattern.{}", format.extension());
+            assert_eq!(
+                PatternFormat::from_extension(Path::new(&path)),
+                Some(format)
+            );
+        }
+        assert_eq!(
+            PatternFormat::from_extension(Path::new("cells.CSV")),
+            Some(PatternFormat::Coordinates)
+        );
+    }
+
+    #[test]
+    fn patterns_round_trip_through_every_format() {
+        let pattern = Pattern::new(vec![
+            Cell::new(1, 0),
+            Cell::new(2, 1),
+            Cell::new(0, 2),
+            Cell::new(1, 2),
+            Cell::new(2, 2),
+        ]);
+        for format in [
+            PatternFormat::Rle,
+            PatternFormat::Plaintext,
+            PatternFormat::Coordinates,
+        ] {
+            let text = format.write(&PatternDocument::new(pattern.clone()));
+            assert_eq!(
+                format.parse(&text).unwrap().pattern,
+                pattern,
+                "{:?}",
+                format
+            );
+            assert_eq!(PatternFormat::detect(&text), format, "{:?}", format);
+        }
+    }
+}
@@ -0,0 +1,456 @@
+use crate::{Cell, LifeEngine};
+use std::collections::VecDeque;
+
+/// A saved world: the generation counter and the alive cells.
+#[derive(PartialEq, Eq, Debug, Clone)]
+struct State {
+    generation: u64,
+    cells: Vec<Cell>,
+}
+
+impl State {
+    fn capture(engine: &LifeEngine) -> State {
+        State {
+            generation: engine.get_generation(),
+            cells: engine.get_alive_cells().cloned().collect(),
+        }
+    }
+
+    fn apply(&self, engine: &mut LifeEngine) {
+        engine.restore(self.generation, &self.cells);
+    }
+
+    /// Memory cost of the state, counted in cells. Empty worlds still count as one.
+    fn weight(&self) -> usize {
+        self.cells.len() + 1
+    }
+}
+
+/// A recorded edit: the cells whose state it flipped, in the generation it was made in.
+/// Flipping them again undoes it, and once more redoes it.
+#[derive(PartialEq, Eq, Debug, Clone)]
+struct Change {
+    generation: u64,
+    cells: Vec<Cell>,
+}
+
+impl Change {
+    /// Memory cost of the change, counted in cells like a `State`.
+    fn weight(&self) -> usize {
+        self.cells.len() + 1
+    }
+}
+
+/// Undo and redo of edits, and stepping back to earlier generations, for a `LifeEngine`.
+///
+/// Edits and steps have to go through `edit` and `step` to be recorded. Every edit keeps
+/// the cells it changed on the undo stack. Generations are not stored one by one: the world
+/// is checkpointed every `checkpoint_interval` generations and when stepping away from a
+/// generation that was edited, and an earlier generation is recomputed from the closest
+/// checkpoint before it. Undoing an edit made in an earlier generation first goes back to
+/// the checkpoint of that generation.
+///
+/// The total number of cells kept across all saved worlds and edits is bounded by
+/// `cell_limit`; the oldest checkpoints, with the edits that can no longer be undone without
+/// them, then the oldest undo entries, are dropped to stay under it.
+#[derive(Debug)]
+pub struct History {
+    undo: VecDeque<Change>,
+    redo: Vec<Change>,
+    checkpoints: VecDeque<State>,
+    /// Whether the current generation was edited since its checkpoint, if any, was taken.
+    is_checkpoint_due: bool,
+    undo_limit: usize,
+    checkpoint_interval: u64,
+    cell_limit: usize,
+}
+
+impl History {
+    pub fn new() -> History {
+        History {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            checkpoints: VecDeque::new(),
+            is_checkpoint_due: false,
+            undo_limit: 100,
+            checkpoint_interval: 32,
+            cell_limit: 10_000_000,
+        }
+    }
+
+    /// Sets how many edits can be undone.
+    pub fn with_undo_limit(mut self, undo_limit: usize) -> History {
+        self.undo_limit = undo_limit;
+        self
+    }
+
+    /// Sets how many generations apart checkpoints are taken. Stepping back recomputes
+    /// up to this many generations.
+    pub fn with_checkpoint_interval(mut self, checkpoint_interval: u64) -> History {
+        self.checkpoint_interval = checkpoint_interval.max(1);
+        self
+    }
+
+    /// Sets the total number of cells that may be kept across all saved worlds and edits.
+    pub fn with_cell_limit(mut self, cell_limit: usize) -> History {
+        self.cell_limit = cell_limit;
+        self
+    }
+
+    /// Applies an edit to the engine and records it so that it can be undone. The edit may
+    /// only change cells. Edits that leave every cell as it was, such as failed ones, are not
+    /// recorded.
+    pub fn edit<T>(
+        &mut self,
+        engine: &mut LifeEngine,
+        edit: impl FnOnce(&mut LifeEngine) -> T,
+    ) -> T {
+        engine.start_journal();
+        let result = edit(engine);
+        let cells = engine.take_journal();
+        if cells.is_empty() {
+            return result;
+        }
+        let generation = engine.get_generation();
+        self.redo.clear();
+        self.undo.push_back(Change { generation, cells });
+        if self.undo.len() > self.undo_limit {
+            self.undo.pop_front();
+        }
+        self.invalidate_checkpoints(generation);
+        self.enforce_cell_limit();
+        result
+    }
+
+    /// Computes the next generation, checkpointing the current one when it is due.
+    pub fn step(&mut self, engine: &mut LifeEngine) {
+        let generation = engine.get_generation();
+        let is_checkpoint_due = self.is_checkpoint_due
+            || self
+                .checkpoints
+                .back()
+                .is_none_or(|last| generation >= last.generation + self.checkpoint_interval);
+        if is_checkpoint_due {
+            self.checkpoints.push_back(State::capture(engine));
+            self.is_checkpoint_due = false;
+            self.enforce_cell_limit();
+        }
+        self.redo.clear();
+        engine.next();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Restores the world from before the last edit, going back to the generation it was made
+    /// in. Returns false if there is nothing to undo.
+    pub fn undo(&mut self, engine: &mut LifeEngine) -> bool {
+        let Some(change) = self.undo.pop_back() else {
+            return false;
+        };
+        if engine.get_generation() != change.generation {
+            // Stepping away from an edited generation always checkpoints it, and its edits
+            // are dropped together with the checkpoint.
+            let Some(checkpoint) = self
+                .checkpoints
+                .iter()
+                .rfind(|state| state.generation == change.generation)
+            else {
+                self.undo.clear();
+                return false;
+            };
+            checkpoint.apply(engine);
+            // Edits undone in later generations can't be redone from this one.
+            self.redo.clear();
+        }
+        engine.flip_cells(&change.cells);
+        self.invalidate_checkpoints(change.generation);
+        self.redo.push(change);
+        true
+    }
+
+    /// Applies the edit undone last again. Returns false if there is nothing to redo.
+    pub fn redo(&mut self, engine: &mut LifeEngine) -> bool {
+        // Steps and undoing across generations clear the redo stack, so its edits were made
+        // in the current generation.
+        let Some(change) = self.redo.pop() else {
+            return false;
+        };
+        engine.flip_cells(&change.cells);
+        self.invalidate_checkpoints(change.generation);
+        self.undo.push_back(change);
+        true
+    }
+
+    /// Goes back to the previous generation. Returns false if it is older than the
+    /// oldest checkpoint kept.
+    pub fn step_back(&mut self, engine: &mut LifeEngine) -> bool {
+        let Some(target) = engine.get_generation().checked_sub(1) else {
+            return false;
+        };
+        while self
+            .checkpoints
+            .back()
+            .is_some_and(|last| last.generation > target)
+        {
+            self.checkpoints.pop_back();
+        }
+        let Some(checkpoint) = self.checkpoints.back() else {
+            return false;
+        };
+        checkpoint.apply(engine);
+        while engine.get_generation() < target {
+            engine.next();
+        }
+        // Edits of the generations after the target no longer happen.
+        while self
+            .undo
+            .back()
+            .is_some_and(|change| change.generation > target)
+        {
+            self.undo.pop_back();
+        }
+        self.redo.clear();
+        self.is_checkpoint_due = false;
+        true
+    }
+
+    /// Generation of the oldest checkpoint, the furthest `step_back` can go.
+    pub fn get_oldest_generation(&self) -> Option<u64> {
+        self.checkpoints.front().map(|state| state.generation)
+    }
+
+    /// Total number of cells kept across all saved worlds.
+    pub fn get_stored_cells(&self) -> usize {
+        let changes: usize = self
+            .undo
+            .iter()
+            .chain(self.redo.iter())
+            .map(Change::weight)
+            .sum();
+        changes + self.checkpoints.iter().map(State::weight).sum::<usize>()
+    }
+
+    /// Forgets everything, e.g. when the engine is replaced by a new world.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.checkpoints.clear();
+        self.is_checkpoint_due = false;
+    }
+
+    /// Called after the world of `generation` was edited: checkpoints of it or later describe
+    /// a timeline that no longer happens and are dropped, and a new one is taken when
+    /// stepping away from it.
+    fn invalidate_checkpoints(&mut self, generation: u64) {
+        while self
+            .checkpoints
+            .back()
+            .is_some_and(|last| last.generation >= generation)
+        {
+            self.checkpoints.pop_back();
+        }
+        self.is_checkpoint_due = true;
+    }
+
+    fn enforce_cell_limit(&mut self) {
+        let mut stored_cells = self.get_stored_cells();
+        while stored_cells > self.cell_limit {
+            if self.checkpoints.len() > 1
+                && let Some(state) = self.checkpoints.pop_front()
+            {
+                stored_cells -= state.weight();
+                // Edits of earlier generations are undone from their checkpoint.
+                while self
+                    .undo
+                    .front()
+                    .is_some_and(|change| change.generation <= state.generation)
+                {
+                    stored_cells -= self.undo.pop_front().map_or(0, |c| c.weight());
+                }
+                continue;
+            }
+            let dropped = if !self.undo.is_empty() {
+                self.undo.pop_front()
+            } else if !self.redo.is_empty() {
+                Some(self.redo.remove(0))
+            } else {
+                None
+            };
+            match dropped {
+                Some(change) => stored_cells -= change.weight(),
+                None => break,
+            }
+        }
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Region;
+
+    fn glider() -> LifeEngine {
+        let mut engine = LifeEngine::new(20, 20);
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            engine.activate_cell(x, y).unwrap();
+        }
+        engine
+    }
+
+    fn sorted_cells(engine: &LifeEngine) -> Vec<Cell> {
+        let mut cells: Vec<Cell> = engine.get_alive_cells().cloned().collect();
+        cells.sort();
+        cells
+    }
+
+    #[test]
+    fn edits_are_undone_and_redone() {
+        let mut engine = glider();
+        let mut history = History::new();
+        let before = sorted_cells(&engine);
+        history
+            .edit(&mut engine, |e| e.activate_cell(10, 10))
+            .unwrap();
+        history
+            .edit(&mut engine, |e| e.deactivate_cell(1, 0))
+            .unwrap();
+        let after = sorted_cells(&engine);
+
+        assert!(history.undo(&mut engine));
+        assert!(engine.is_cell_alive(1, 0) && engine.is_cell_alive(10, 10));
+        assert!(history.undo(&mut engine));
+        assert_eq!(sorted_cells(&engine), before);
+        assert!(!history.undo(&mut engine));
+
+        assert!(history.redo(&mut engine));
+        assert!(history.redo(&mut engine));
+        assert_eq!(sorted_cells(&engine), after);
+        assert!(!history.redo(&mut engine));
+    }
+
+    #[test]
+    fn edits_changing_nothing_are_not_recorded() {
+        let mut engine = glider();
+        let mut history = History::new();
+        history
+            .edit(&mut engine, |e| e.activate_cell(5, 5))
+            .unwrap();
+        assert!(history.undo(&mut engine));
+
+        assert!(
+            history
+                .edit(&mut engine, |e| e.activate_cell(50, 5))
+                .is_err()
+        );
+        history
+            .edit(&mut engine, |e| e.activate_cell(1, 0))
+            .unwrap();
+        // Flipping a cell twice leaves it as it was.
+        history.edit(&mut engine, |e| {
+            e.invert_region(&Region::new(8, 8, 2, 2));
+            e.invert_region(&Region::new(8, 8, 2, 2));
+        });
+        assert!(!history.can_undo());
+        assert!(history.can_redo());
+    }
+
+    #[test]
+    fn edits_keep_only_the_changed_cells() {
+        let mut engine = LifeEngine::new(120, 120);
+        engine.generate_random_square(Cell::new(0, 0), 100);
+        let corner = Region::new(0, 0, 2, 2);
+        let cleared = engine.get_region(&corner).get_alive_cells_count();
+        let mut history = History::new();
+        history.edit(&mut engine, |e| e.clear_region(&corner));
+        history
+            .edit(&mut engine, |e| e.activate_cell(110, 110))
+            .unwrap();
+        history
+            .edit(&mut engine, |e| e.deactivate_cell(110, 110))
+            .unwrap();
+        let recorded_edits = if cleared > 0 { 3 } else { 2 };
+        assert_eq!(history.get_stored_cells(), cleared + 2 + recorded_edits);
+    }
+
+    #[test]
+    fn undo_goes_back_to_the_generation_of_the_edit() {
+        let mut engine = glider();
+        let mut history = History::new();
+        let before = sorted_cells(&engine);
+        history
+            .edit(&mut engine, |e| e.activate_cell(15, 15))
+            .unwrap();
+        history
+            .edit(&mut engine, |e| e.activate_cell(15, 16))
+            .unwrap();
+        for _ in 0..5 {
+            history.step(&mut engine);
+        }
+
+        assert!(history.undo(&mut engine));
+        assert_eq!(engine.get_generation(), 0);
+        assert!(engine.is_cell_alive(15, 15) && !engine.is_cell_alive(15, 16));
+        assert!(history.undo(&mut engine));
+        assert_eq!(sorted_cells(&engine), before);
+        assert!(history.redo(&mut engine));
+        assert!(engine.is_cell_alive(15, 15) && !engine.is_cell_alive(15, 16));
+    }
+
+    #[test]
+    fn stepping_back_replays_edits() {
+        let mut engine = glider();
+        let mut history = History::new().with_checkpoint_interval(100);
+        for _ in 0..3 {
+            history.step(&mut engine);
+        }
+        history
+            .edit(&mut engine, |e| e.activate_cell(0, 0))
+            .unwrap();
+        let edited = sorted_cells(&engine);
+        for _ in 0..3 {
+            history.step(&mut engine);
+        }
+
+        for _ in 0..3 {
+            assert!(history.step_back(&mut engine));
+        }
+        assert_eq!(engine.get_generation(), 3);
+        assert_eq!(sorted_cells(&engine), edited);
+        assert!(history.can_undo());
+
+        // Going back before the edit forgets it.
+        assert!(history.step_back(&mut engine));
+        assert!(!history.can_undo());
+        history.step(&mut engine);
+        assert!(!engine.is_cell_alive(0, 0));
+    }
+
+    #[test]
+    fn cell_limit_drops_old_checkpoints_with_their_edits() {
+        let mut engine = glider();
+        let mut history = History::new()
+            .with_checkpoint_interval(1)
+            .with_cell_limit(40);
+        history
+            .edit(&mut engine, |e| e.activate_cell(15, 15))
+            .unwrap();
+        for _ in 0..20 {
+            history.step(&mut engine);
+            assert!(history.get_stored_cells() <= 40);
+        }
+        assert!(!history.can_undo());
+        assert_eq!(history.get_oldest_generation(), Some(14));
+    }
+}
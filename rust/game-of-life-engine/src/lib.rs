@@ -1,9 +1,27 @@
 use rand::RngExt;
-use rustc_hash::{FxBuildHasher, FxHashSet};
-use std::cmp::min;
+use rustc_hash::{FxBuildHasher, FxHashMap, FxHashSet};
+use std::cmp::{max, min};
 use std::collections::HashSet;
 use std::collections::hash_set::Iter;
 
+pub mod activity;
+#[cfg(feature = "gif")]
+pub mod animation;
+pub mod catalogue;
+pub mod format;
+pub mod history;
+pub mod pattern;
+pub mod render;
+pub mod rule;
+#[cfg(feature = "share")]
+pub mod share;
+pub mod topology;
+
+pub use activity::HeatMap;
+pub use pattern::Pattern;
+pub use rule::Rule;
+pub use topology::Topology;
+
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone)]
 #[repr(C)]
 pub struct Cell {
@@ -17,12 +35,65 @@ impl Cell {
     }
 }
 
-#[derive(PartialEq, Eq, Debug)]
+/// A rectangle of cells, given by its top left cell and its size in cells.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Region {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Region {
+        Region {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Creates the smallest region containing both corners, given in any order.
+    pub fn from_corners(a: &Cell, b: &Cell) -> Region {
+        let x = min(a.x, b.x);
+        let y = min(a.y, b.y);
+        Region::new(x, y, max(a.x, b.x) - x + 1, max(a.y, b.y) - y + 1)
+    }
+
+    pub fn contains(&self, cell: &Cell) -> bool {
+        cell.x >= self.x
+            && cell.y >= self.y
+            && cell.x - self.x < self.width
+            && cell.y - self.y < self.height
+    }
+
+    /// Iterates every cell of the region, row by row.
+    pub fn cells(&self) -> impl Iterator<Item = Cell> + '_ {
+        (self.y..self.y.saturating_add(self.height)).flat_map(move |y| {
+            (self.x..self.x.saturating_add(self.width)).map(move |x| Cell::new(x, y))
+        })
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
 pub struct LifeEngine {
     cols: u32,
     rows: u32,
+    rule: Rule,
+    topology: Topology,
+    generation: u64,
     alive_cells: FxHashSet<Cell>,
     potential_cells: FxHashSet<Cell>,
+    last_births: Vec<Cell>,
+    last_deaths: Vec<Cell>,
+    /// Generation in which each alive cell was born, when age tracking is enabled.
+    birth_generations: Option<FxHashMap<Cell, u64>>,
+    heat_map: Option<HeatMap>,
+    /// Cells whose state was flipped by edits since the journal was started, kept while
+    /// the history records an edit.
+    journal: Option<FxHashSet<Cell>>,
 }
 
 impl LifeEngine {
@@ -30,41 +101,116 @@ impl LifeEngine {
         LifeEngine {
             cols,
             rows,
+            rule: Rule::default(),
+            topology: Topology::default(),
+            generation: 0,
             alive_cells: FxHashSet::default(),
             potential_cells: FxHashSet::default(),
+            last_births: Vec::new(),
+            last_deaths: Vec::new(),
+            birth_generations: None,
+            heat_map: None,
+            journal: None,
         }
     }
 
     pub fn with_initial_cells(cols: u32, rows: u32, initial_cells: HashSet<Cell>) -> LifeEngine {
         let mut alive_cells =
-            FxHashSet::with_capacity_and_hasher(initial_cells.len(), FxBuildHasher::default());
+            FxHashSet::with_capacity_and_hasher(initial_cells.len(), FxBuildHasher);
         let potential_cells =
-            FxHashSet::with_capacity_and_hasher(initial_cells.len() * 8, FxBuildHasher::default());
+            FxHashSet::with_capacity_and_hasher(initial_cells.len() * 8, FxBuildHasher);
 
         alive_cells.extend(initial_cells);
 
         let mut engine = LifeEngine {
             cols,
             rows,
+            rule: Rule::default(),
+            topology: Topology::default(),
+            generation: 0,
             alive_cells,
             potential_cells,
+            last_births: Vec::new(),
+            last_deaths: Vec::new(),
+            birth_generations: None,
+            heat_map: None,
+            journal: None,
         };
-
-        let mut neighbours: Vec<Cell> = Vec::with_capacity(8);
-        for cell in engine.alive_cells.iter() {
-            engine.potential_cells.insert(cell.clone());
-            engine.get_neighbours(cell, &mut neighbours);
-            for neighbour in neighbours.iter() {
-                engine.potential_cells.insert(neighbour.clone());
-            }
-        }
+        engine.rebuild_potential_cells();
         engine
     }
 
+    pub fn get_cols(&self) -> u32 {
+        self.cols
+    }
+
+    pub fn get_rows(&self) -> u32 {
+        self.rows
+    }
+
+    /// Number of times `next` was called on this engine.
+    pub fn get_generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn get_rule(&self) -> Rule {
+        self.rule
+    }
+
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.rule = rule;
+        // Cells that were stable under the old rule may change under the new one.
+        self.rebuild_potential_cells();
+    }
+
+    pub fn get_topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology;
+        // Cells on the edges gain or lose neighbours.
+        self.rebuild_potential_cells();
+    }
+
+    /// Enables or disables tracking how many generations each alive cell has lived.
+    /// Cells alive when tracking starts count as born in the current generation.
+    pub fn set_age_tracking(&mut self, is_enabled: bool) {
+        self.birth_generations = is_enabled.then(|| {
+            self.alive_cells
+                .iter()
+                .map(|c| (c.clone(), self.generation))
+                .collect()
+        });
+    }
+
+    pub fn is_age_tracking(&self) -> bool {
+        self.birth_generations.is_some()
+    }
+
+    /// Returns how many generations the cell has been alive for, 0 for a cell born in
+    /// the current generation. `None` if the cell is dead or age tracking is disabled.
+    pub fn get_cell_age(&self, cell: &Cell) -> Option<u64> {
+        self.birth_generations
+            .as_ref()?
+            .get(cell)
+            .map(|born| self.generation - born)
+    }
+
+    /// Enables the heat map with the given per generation decay, or disables it with `None`.
+    pub fn set_heat_map(&mut self, decay: Option<f32>) -> Result<(), String> {
+        self.heat_map = decay.map(HeatMap::new).transpose()?;
+        Ok(())
+    }
+
+    pub fn get_heat_map(&self) -> Option<&HeatMap> {
+        self.heat_map.as_ref()
+    }
+
     pub fn activate_cells(&mut self, cells: &[Cell]) -> Result<(), String> {
-        let any_out_of_bounds = cells.iter().any(|c| self.is_cell_within_bounds(c));
+        let any_out_of_bounds = cells.iter().any(|c| !self.is_cell_within_bounds(c));
         if any_out_of_bounds {
-            return Err(String::from("some cells out of bounds"))
+            return Err(String::from("some cells out of bounds"));
         }
         self.alive_cells.reserve(cells.len());
         self.potential_cells.reserve(cells.len() * 8);
@@ -83,25 +229,109 @@ impl LifeEngine {
         Ok(())
     }
 
+    /// Activates the cells of `pattern` placed with its top left corner at `top_left`.
+    /// Cells that fall outside the grid are skipped.
+    pub fn stamp_pattern(&mut self, pattern: &Pattern, top_left: Cell) {
+        self.alive_cells.reserve(pattern.get_alive_cells_count());
+        self.potential_cells
+            .reserve(pattern.get_alive_cells_count() * 8);
+        for cell in pattern.cells_at(&top_left) {
+            if self.is_cell_within_bounds(&cell) {
+                self.do_activate_cell(&cell);
+            }
+        }
+    }
+
+    pub fn deactivate_cell(&mut self, x: u32, y: u32) -> Result<(), String> {
+        let cell = Cell::new(x, y);
+        if !(self.is_cell_within_bounds(&cell)) {
+            return Err(String::from("cell out of bounds"));
+        }
+        self.do_deactivate_cell(&cell);
+        Ok(())
+    }
+
+    pub fn is_cell_alive(&self, x: u32, y: u32) -> bool {
+        self.alive_cells.contains(&Cell::new(x, y))
+    }
+
+    /// Returns the alive cells inside `region` as a pattern.
+    pub fn get_region(&self, region: &Region) -> Pattern {
+        Pattern::from_region(region, self.alive_cells.iter())
+    }
+
+    /// Kills every cell inside `region`.
+    pub fn clear_region(&mut self, region: &Region) {
+        let cells: Vec<Cell> = self
+            .alive_cells
+            .iter()
+            .filter(|c| region.contains(c))
+            .cloned()
+            .collect();
+        for cell in cells {
+            self.do_deactivate_cell(&cell);
+        }
+    }
+
+    /// Flips the state of every cell inside `region` that lies within the grid.
+    pub fn invert_region(&mut self, region: &Region) {
+        for cell in region.cells() {
+            if !self.is_cell_within_bounds(&cell) {
+                continue;
+            }
+            if self.alive_cells.contains(&cell) {
+                self.do_deactivate_cell(&cell);
+            } else {
+                self.do_activate_cell(&cell);
+            }
+        }
+    }
+
+    /// Replaces the contents of `region` with random cells, each alive with probability `density`.
+    pub fn fill_region_randomly(&mut self, region: &Region, density: f64) {
+        self.clear_region(region);
+        let mut rng = rand::rng();
+        for cell in region.cells() {
+            if self.is_cell_within_bounds(&cell) && rng.random_bool(density.clamp(0.0, 1.0)) {
+                self.do_activate_cell(&cell);
+            }
+        }
+    }
+
     fn do_activate_cell(&mut self, cell: &Cell) {
-        self.alive_cells.insert(cell.clone());
+        if self.alive_cells.insert(cell.clone()) {
+            self.record_flip(cell);
+        }
+        if let Some(birth_generations) = self.birth_generations.as_mut() {
+            birth_generations
+                .entry(cell.clone())
+                .or_insert(self.generation);
+        }
         self.potential_cells.insert(cell.clone());
         let mut neighbours = Vec::with_capacity(8);
-        self.get_neighbours(&cell, &mut neighbours);
+        self.get_neighbours(cell, &mut neighbours);
         for neighbour in neighbours {
             self.potential_cells.insert(neighbour);
         }
     }
 
+    fn do_deactivate_cell(&mut self, cell: &Cell) {
+        if !self.alive_cells.remove(cell) {
+            return;
+        }
+        self.record_flip(cell);
+        if let Some(birth_generations) = self.birth_generations.as_mut() {
+            birth_generations.remove(cell);
+        }
+        self.potential_cells.insert(cell.clone());
+        let mut neighbours = Vec::with_capacity(8);
+        self.get_neighbours(cell, &mut neighbours);
+        self.potential_cells.extend(neighbours);
+    }
+
     pub fn next(&mut self) {
-        let mut alive_cells_next: FxHashSet<Cell> = FxHashSet::with_capacity_and_hasher(
-            self.alive_cells.capacity(),
-            FxBuildHasher::default(),
-        );
-        let mut potential_cells_next: FxHashSet<Cell> = FxHashSet::with_capacity_and_hasher(
-            self.potential_cells.capacity(),
-            FxBuildHasher::default(),
-        );
+        let mut births: Vec<Cell> = Vec::with_capacity(self.last_births.len());
+        let mut deaths: Vec<Cell> = Vec::with_capacity(self.last_deaths.len());
 
         let mut neighbours = Vec::with_capacity(8);
         for cell in &self.potential_cells {
@@ -112,27 +342,43 @@ impl LifeEngine {
                 .filter(|c| self.alive_cells.contains(c))
                 .count();
             if is_alive {
-                if alive_neighbours_count == 2 || alive_neighbours_count == 3 {
-                    alive_cells_next.insert(cell.clone());
-                } else {
-                    potential_cells_next.insert(cell.clone());
-                    neighbours.iter().for_each(|c| {
-                        potential_cells_next.insert(c.clone());
-                    });
-                }
-            } else {
-                if alive_neighbours_count == 3 {
-                    alive_cells_next.insert(cell.clone());
-                    potential_cells_next.insert(cell.clone());
-                    neighbours.iter().for_each(|c| {
-                        potential_cells_next.insert(c.clone());
-                    });
+                if !self.rule.survives(alive_neighbours_count) {
+                    deaths.push(cell.clone());
                 }
+            } else if self.rule.is_born(alive_neighbours_count) {
+                births.push(cell.clone());
             }
         }
 
-        self.alive_cells = alive_cells_next;
+        // Only cells whose state changed can affect the next generation, so the potential
+        // cells are rebuilt from them. Alive cells outside of that set are carried over as is.
+        let mut potential_cells_next: FxHashSet<Cell> =
+            FxHashSet::with_capacity_and_hasher(self.potential_cells.capacity(), FxBuildHasher);
+        for cell in &deaths {
+            self.alive_cells.remove(cell);
+        }
+        self.alive_cells.extend(births.iter().cloned());
+        for cell in births.iter().chain(deaths.iter()) {
+            potential_cells_next.insert(cell.clone());
+            self.get_neighbours(cell, &mut neighbours);
+            potential_cells_next.extend(neighbours.drain(..));
+        }
+
         self.potential_cells = potential_cells_next;
+        self.generation += 1;
+        if let Some(birth_generations) = self.birth_generations.as_mut() {
+            for cell in &deaths {
+                birth_generations.remove(cell);
+            }
+            for cell in &births {
+                birth_generations.insert(cell.clone(), self.generation);
+            }
+        }
+        if let Some(heat_map) = self.heat_map.as_mut() {
+            heat_map.record(&births, &deaths);
+        }
+        self.last_births = births;
+        self.last_deaths = deaths;
     }
 
     pub fn generate_random_square(&mut self, top_left: Cell, size: u32) {
@@ -167,13 +413,131 @@ impl LifeEngine {
         self.alive_cells.len()
     }
 
+    /// Cells that were born during the last call to `next`.
+    pub fn get_last_births(&self) -> &[Cell] {
+        &self.last_births
+    }
+
+    /// Cells that died during the last call to `next`.
+    pub fn get_last_deaths(&self) -> &[Cell] {
+        &self.last_deaths
+    }
+
+    /// Returns the top left and bottom right corners of the smallest rectangle
+    /// containing every alive cell, or `None` if there are no alive cells.
+    pub fn get_bounding_box(&self) -> Option<(Cell, Cell)> {
+        let mut cells = self.alive_cells.iter();
+        let first = cells.next()?;
+        let (top_left, bottom_right) = cells.fold(
+            (first.clone(), first.clone()),
+            |(top_left, bottom_right), c| {
+                (
+                    Cell::new(min(top_left.x, c.x), min(top_left.y, c.y)),
+                    Cell::new(max(bottom_right.x, c.x), max(bottom_right.y, c.y)),
+                )
+            },
+        );
+        Some((top_left, bottom_right))
+    }
+
+    /// Returns the alive cells as a pattern, together with the position of its top left corner.
+    pub fn to_pattern(&self) -> (Cell, Pattern) {
+        let top_left = self
+            .get_bounding_box()
+            .map_or(Cell::new(0, 0), |(top_left, _)| top_left);
+        (
+            top_left,
+            Pattern::new(self.alive_cells.iter().cloned().collect()),
+        )
+    }
+
+    /// Replaces the generation counter and the alive cells, e.g. with a state kept by the history.
+    fn restore(&mut self, generation: u64, cells: &[Cell]) {
+        self.generation = generation;
+        self.alive_cells = cells.iter().cloned().collect();
+        self.last_births.clear();
+        self.last_deaths.clear();
+        if self.birth_generations.is_some() {
+            self.set_age_tracking(true);
+        }
+        self.rebuild_potential_cells();
+    }
+
+    /// Flips the state of each of `cells`, e.g. to undo an edit recorded by the history.
+    fn flip_cells(&mut self, cells: &[Cell]) {
+        for cell in cells {
+            if self.alive_cells.contains(cell) {
+                self.do_deactivate_cell(cell);
+            } else {
+                self.do_activate_cell(cell);
+            }
+        }
+        self.last_births.clear();
+        self.last_deaths.clear();
+    }
+
+    /// Starts recording the cells flipped by edits.
+    fn start_journal(&mut self) {
+        self.journal = Some(FxHashSet::default());
+    }
+
+    /// Stops recording, returning the cells whose state differs from when the journal was started.
+    fn take_journal(&mut self) -> Vec<Cell> {
+        self.journal
+            .take()
+            .map(|cells| cells.into_iter().collect())
+            .unwrap_or_default()
+    }
+
+    fn record_flip(&mut self, cell: &Cell) {
+        // A cell flipped twice is back to its old state.
+        if let Some(journal) = self.journal.as_mut()
+            && !journal.remove(cell)
+        {
+            journal.insert(cell.clone());
+        }
+    }
+
+    fn rebuild_potential_cells(&mut self) {
+        self.potential_cells.clear();
+        self.potential_cells.reserve(self.alive_cells.len() * 8);
+        let mut neighbours: Vec<Cell> = Vec::with_capacity(8);
+        for cell in self.alive_cells.iter() {
+            self.potential_cells.insert(cell.clone());
+            self.get_neighbours(cell, &mut neighbours);
+            self.potential_cells.extend(neighbours.drain(..));
+        }
+    }
+
     fn get_neighbours(&self, cell: &Cell, container: &mut Vec<Cell>) {
         container.clear();
+        if self.topology == Topology::Torus {
+            let left = cell.x.checked_sub(1).unwrap_or(self.cols.saturating_sub(1));
+            let right = cell
+                .x
+                .checked_add(1)
+                .filter(|x| *x < self.cols)
+                .unwrap_or(0);
+            let top = cell.y.checked_sub(1).unwrap_or(self.rows.saturating_sub(1));
+            let bottom = cell
+                .y
+                .checked_add(1)
+                .filter(|y| *y < self.rows)
+                .unwrap_or(0);
+            for x in [left, cell.x, right] {
+                for y in [top, cell.y, bottom] {
+                    if x != cell.x || y != cell.y {
+                        container.push(Cell::new(x, y));
+                    }
+                }
+            }
+            return;
+        }
         for dx in -1i32..=1i32 {
             if dx == -1 && cell.x == 0 {
                 continue;
             }
-            if dx == 1 && cell.x == self.cols - 1 {
+            if dx == 1 && cell.x.saturating_add(1) >= self.cols {
                 continue;
             }
             for dy in -1i32..=1i32 {
@@ -183,7 +547,7 @@ impl LifeEngine {
                 if dy == -1 && cell.y == 0 {
                     continue;
                 }
-                if dy == 1 && cell.y == self.rows - 1 {
+                if dy == 1 && cell.y.saturating_add(1) >= self.rows {
                     continue;
                 }
                 container.push(Cell::new(
@@ -198,3 +562,163 @@ impl LifeEngine {
         cell.x < self.cols && cell.y < self.rows
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine_with(cols: u32, rows: u32, cells: &[(u32, u32)]) -> LifeEngine {
+        let mut engine = LifeEngine::new(cols, rows);
+        for (x, y) in cells {
+            engine.activate_cell(*x, *y).unwrap();
+        }
+        engine
+    }
+
+    fn sorted_cells(engine: &LifeEngine) -> Vec<Cell> {
+        let mut cells: Vec<Cell> = engine.get_alive_cells().cloned().collect();
+        cells.sort();
+        cells
+    }
+
+    /// The next generation computed by counting the neighbours of every cell of the grid,
+    /// wrapping around the edges on a torus.
+    fn full_scan_next(engine: &LifeEngine) -> Vec<Cell> {
+        let is_torus = engine.get_topology() == Topology::Torus;
+        let (cols, rows) = (engine.get_cols() as i64, engine.get_rows() as i64);
+        let rule = engine.get_rule();
+        let mut next = Vec::new();
+        for y in 0..rows {
+            for x in 0..cols {
+                let mut count = 0;
+                for (dx, dy) in (-1..=1).flat_map(|dx| (-1..=1).map(move |dy| (dx, dy))) {
+                    let (mut nx, mut ny) = (x + dx, y + dy);
+                    if is_torus {
+                        (nx, ny) = (nx.rem_euclid(cols), ny.rem_euclid(rows));
+                    }
+                    if (dx, dy) != (0, 0)
+                        && (0..cols).contains(&nx)
+                        && (0..rows).contains(&ny)
+                        && engine.is_cell_alive(nx as u32, ny as u32)
+                    {
+                        count += 1;
+                    }
+                }
+                let is_alive = engine.is_cell_alive(x as u32, y as u32);
+                if (is_alive && rule.survives(count)) || (!is_alive && rule.is_born(count)) {
+                    next.push(Cell::new(x as u32, y as u32));
+                }
+            }
+        }
+        next.sort();
+        next
+    }
+
+    fn assert_matches_full_scan(mut engine: LifeEngine, generations: u32) {
+        for generation in 1..=generations {
+            let expected = full_scan_next(&engine);
+            engine.next();
+            assert_eq!(sorted_cells(&engine), expected, "generation {}", generation);
+        }
+    }
+
+    #[test]
+    fn activate_cells_rejects_only_cells_outside_the_grid() {
+        let mut engine = LifeEngine::new(4, 4);
+        let inside = [Cell::new(0, 0), Cell::new(3, 3)];
+        assert!(engine.activate_cells(&inside).is_ok());
+        assert_eq!(engine.get_alive_cells_count(), 2);
+        for outside in [Cell::new(4, 0), Cell::new(0, 4)] {
+            assert!(engine.activate_cells(&[Cell::new(1, 1), outside]).is_err());
+        }
+        assert_eq!(engine.get_alive_cells_count(), 2);
+    }
+
+    #[test]
+    fn still_lifes_survive() {
+        let mut engine = engine_with(10, 10, &[(1, 1), (2, 1), (1, 2), (2, 2)]);
+        for _ in 0..5 {
+            engine.next();
+        }
+        assert_eq!(engine.get_alive_cells_count(), 4);
+        assert_eq!(engine.get_generation(), 5);
+    }
+
+    #[test]
+    fn blinker_matches_full_scan() {
+        assert_matches_full_scan(engine_with(5, 5, &[(1, 2), (2, 2), (3, 2)]), 10);
+    }
+
+    #[test]
+    fn glider_matches_full_scan() {
+        // Runs into the bottom right corner, where it turns into a block.
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        assert_matches_full_scan(engine_with(12, 12, &glider), 60);
+    }
+
+    #[test]
+    fn random_soups_match_full_scan() {
+        for size in [8, 20, 40] {
+            let mut engine = LifeEngine::new(48, 48);
+            engine.generate_random_square(Cell::new(4, 4), size);
+            assert_matches_full_scan(engine, 80);
+        }
+    }
+
+    #[test]
+    fn other_rules_match_full_scan() {
+        let mut engine = LifeEngine::new(32, 32);
+        engine.set_rule("B36/S23".parse().unwrap());
+        engine.generate_random_square(Cell::new(4, 4), 24);
+        assert_matches_full_scan(engine, 40);
+    }
+
+    #[test]
+    fn glider_wraps_around_a_torus() {
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let mut engine = engine_with(8, 8, &glider);
+        engine.set_topology(Topology::Torus);
+        let start = sorted_cells(&engine);
+        // A glider moves by one cell diagonally every 4 generations.
+        for _ in 0..4 * 8 {
+            engine.next();
+            assert_eq!(engine.get_alive_cells_count(), 5);
+        }
+        assert_eq!(sorted_cells(&engine), start);
+    }
+
+    #[test]
+    fn torus_matches_full_scan() {
+        let mut engine = LifeEngine::new(24, 16);
+        engine.set_topology(Topology::Torus);
+        engine.generate_random_square(Cell::new(10, 4), 14);
+        assert_matches_full_scan(engine, 80);
+
+        // Patterns across the corner, where all four edges meet.
+        let mut engine = engine_with(6, 5, &[(5, 4), (0, 4), (1, 4), (5, 0), (0, 1)]);
+        engine.set_topology(Topology::Torus);
+        assert_matches_full_scan(engine, 20);
+    }
+
+    #[test]
+    fn empty_grids_do_not_panic() {
+        for topology in [Topology::Bounded, Topology::Torus] {
+            let cells = HashSet::from([Cell::new(0, 0)]);
+            let mut engine = LifeEngine::with_initial_cells(0, 0, cells);
+            engine.set_topology(topology);
+            engine.next();
+            assert!(engine.activate_cell(0, 0).is_err());
+        }
+    }
+
+    #[test]
+    fn edits_between_steps_match_full_scan() {
+        let mut engine = engine_with(16, 16, &[(1, 2), (2, 2), (3, 2)]);
+        engine.next();
+        engine.activate_cell(8, 8).unwrap();
+        engine.activate_cell(9, 8).unwrap();
+        engine.activate_cell(10, 8).unwrap();
+        engine.deactivate_cell(2, 1).unwrap();
+        assert_matches_full_scan(engine, 10);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{Cell, Region};
+
+/// A finite arrangement of alive cells, stored as offsets from its top left corner.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Pattern {
+    width: u32,
+    height: u32,
+    cells: Vec<Cell>,
+}
+
+impl Pattern {
+    /// Creates a pattern from cell offsets, trimming empty rows and columns around them.
+    pub fn new(cells: Vec<Cell>) -> Pattern {
+        if cells.is_empty() {
+            return Pattern {
+                width: 0,
+                height: 0,
+                cells,
+            };
+        }
+        let min_x = cells.iter().map(|c| c.x).min().unwrap_or(0);
+        let min_y = cells.iter().map(|c| c.y).min().unwrap_or(0);
+        let max_x = cells.iter().map(|c| c.x).max().unwrap_or(0);
+        let max_y = cells.iter().map(|c| c.y).max().unwrap_or(0);
+
+        let mut cells: Vec<Cell> = cells
+            .into_iter()
+            .map(|c| Cell::new(c.x - min_x, c.y - min_y))
+            .collect();
+        cells.sort_unstable_by_key(|c| (c.y, c.x));
+        cells.dedup();
+
+        Pattern {
+            width: max_x - min_x + 1,
+            height: max_y - min_y + 1,
+            cells,
+        }
+    }
+
+    /// Creates a pattern from the cells that lie inside `region`.
+    pub fn from_region<'a>(region: &Region, cells: impl Iterator<Item = &'a Cell>) -> Pattern {
+        Pattern::new(cells.filter(|c| region.contains(c)).cloned().collect())
+    }
+
+    /// Parses the plaintext format: one line per row, `O` or `*` for alive cells,
+    /// `.` for dead ones, and lines starting with `!` as comments.
+    pub fn from_plaintext(text: &str) -> Result<Pattern, String> {
+        let mut cells = Vec::new();
+        let rows = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('!'));
+        for (y, row) in rows.enumerate() {
+            for (x, symbol) in row.chars().enumerate() {
+                match symbol {
+                    'O' | '*' => cells.push(Cell::new(x as u32, y as u32)),
+                    '.' => {}
+                    _ => {
+                        return Err(format!(
+                            "unexpected symbol '{}' in plaintext pattern",
+                            symbol
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(Pattern::new(cells))
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    pub fn get_alive_cells_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn rotate_clockwise(&self) -> Pattern {
+        self.map_cells(|c| Cell::new(self.height - 1 - c.y, c.x))
+    }
+
+    pub fn rotate_counterclockwise(&self) -> Pattern {
+        self.map_cells(|c| Cell::new(c.y, self.width - 1 - c.x))
+    }
+
+    /// Mirrors the pattern left to right.
+    pub fn flip_horizontal(&self) -> Pattern {
+        self.map_cells(|c| Cell::new(self.width - 1 - c.x, c.y))
+    }
+
+    /// Mirrors the pattern top to bottom.
+    pub fn flip_vertical(&self) -> Pattern {
+        self.map_cells(|c| Cell::new(c.x, self.height - 1 - c.y))
+    }
+
+    /// Iterates the pattern cells translated so that the pattern's top left corner is at `origin`.
+    pub fn cells_at<'a>(&'a self, origin: &'a Cell) -> impl Iterator<Item = Cell> + 'a {
+        self.cells
+            .iter()
+            .map(move |c| Cell::new(origin.x.saturating_add(c.x), origin.y.saturating_add(c.y)))
+    }
+
+    fn map_cells(&self, transform: impl Fn(&Cell) -> Cell) -> Pattern {
+        Pattern::new(self.cells.iter().map(transform).collect())
+    }
+}
@@ -0,0 +1,326 @@
+use crate::{Cell, LifeEngine, Region};
+#[cfg(feature = "png")]
+use std::io::Write;
+#[cfg(feature = "png")]
+use std::path::Path;
+
+/// An RGBA colour, 8 bits per channel.
+pub type Rgba = [u8; 4];
+
+/// The rectangle of cells a frame shows. Viewports are regions of the world, so existing
+/// code naming them `render::Viewport` keeps working.
+pub type Viewport = Region;
+
+/// Parses a `#rrggbb` or `#rrggbbaa` colour.
+pub fn parse_color(value: &str) -> Result<Rgba, String> {
+    let invalid = || format!("invalid colour '{}', expected #rrggbb or #rrggbbaa", value);
+    let hex = value.strip_prefix('#').ok_or_else(invalid)?;
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+    Ok([channel(0)?, channel(2)?, channel(4)?, alpha])
+}
+
+/// A CPU side RGBA image of a viewport, with every cell drawn as a `scale`×`scale` square.
+/// Rows are stored top to bottom without padding, ready to be uploaded as a texture or,
+/// with the `png` feature, written to a PNG file without any window.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Frame {
+    viewport: Viewport,
+    scale: u32,
+    background: Rgba,
+    alive: Rgba,
+    pixels: Vec<u8>,
+}
+
+impl Frame {
+    /// Creates a frame filled with the background colour. Fails if the image would be too
+    /// large to address or to allocate, e.g. for a huge viewport or scale coming from a user.
+    pub fn new(
+        viewport: Viewport,
+        scale: u32,
+        background: Rgba,
+        alive: Rgba,
+    ) -> Result<Frame, String> {
+        let scale = scale.max(1);
+        let (width, height) = Frame::pixel_size(&viewport, scale)?;
+        let too_large = || {
+            format!(
+                "The image would be {}×{} pixels, which is too large",
+                width, height
+            )
+        };
+        let bytes_count = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|count| count.checked_mul(4))
+            .ok_or_else(too_large)?;
+        let mut pixels = Vec::new();
+        pixels
+            .try_reserve_exact(bytes_count)
+            .map_err(|_| too_large())?;
+        for _ in 0..bytes_count / 4 {
+            pixels.extend_from_slice(&background);
+        }
+        Ok(Frame {
+            viewport,
+            scale,
+            background,
+            alive,
+            pixels,
+        })
+    }
+
+    /// Returns the width and height in pixels of a frame of `viewport` with cells `scale`
+    /// pixels wide, or an error if they don't fit into 32 bits.
+    pub fn pixel_size(viewport: &Viewport, scale: u32) -> Result<(u32, u32), String> {
+        let scale = scale.max(1);
+        match (
+            viewport.width.checked_mul(scale),
+            viewport.height.checked_mul(scale),
+        ) {
+            (Some(width), Some(height)) => Ok((width, height)),
+            _ => Err(format!(
+                "The image of {}×{} cells at {} pixels per cell would be too large",
+                viewport.width, viewport.height, scale
+            )),
+        }
+    }
+
+    pub fn viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+
+    /// Width of the image in pixels.
+    pub fn width(&self) -> u32 {
+        self.viewport.width * self.scale
+    }
+
+    /// Height of the image in pixels.
+    pub fn height(&self) -> u32 {
+        self.viewport.height * self.scale
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn get_pixel(&self, x: u32, y: u32) -> Rgba {
+        let offset = (y as usize * self.width() as usize + x as usize) * 4;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.pixels[offset..offset + 4]);
+        pixel
+    }
+
+    pub fn clear(&mut self) {
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&self.background);
+        }
+    }
+
+    /// Redraws the whole frame from the engine's alive cells.
+    pub fn draw_engine(&mut self, engine: &LifeEngine) {
+        self.draw_cells(engine.get_alive_cells());
+    }
+
+    /// Redraws the whole frame from a set of alive cells.
+    pub fn draw_cells<'a>(&mut self, alive_cells: impl IntoIterator<Item = &'a Cell>) {
+        self.clear();
+        for cell in alive_cells {
+            self.set_cell(cell, true);
+        }
+    }
+
+    /// Updates the frame with the cells born and died during the engine's last step.
+    /// Only valid if the frame was up to date with the generation before that step.
+    pub fn apply_last_step(&mut self, engine: &LifeEngine) {
+        self.apply_changes(engine.get_last_births(), engine.get_last_deaths());
+    }
+
+    /// Updates the frame with a set of births and deaths.
+    pub fn apply_changes(&mut self, births: &[Cell], deaths: &[Cell]) {
+        for cell in deaths {
+            self.set_cell(cell, false);
+        }
+        for cell in births {
+            self.set_cell(cell, true);
+        }
+    }
+
+    /// Encodes the frame as an RGBA PNG image.
+    #[cfg(feature = "png")]
+    pub fn write_png(&self, writer: impl Write) -> Result<(), String> {
+        let mut encoder = png::Encoder::new(writer, self.width(), self.height());
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        writer
+            .write_image_data(&self.pixels)
+            .map_err(|e| e.to_string())?;
+        writer.finish().map_err(|e| e.to_string())
+    }
+
+    /// Writes the frame to a PNG file.
+    #[cfg(feature = "png")]
+    pub fn save_png(&self, path: &Path) -> Result<(), String> {
+        let file = std::fs::File::create(path)
+            .map_err(|e| format!("Unable to create {}: {}", path.display(), e))?;
+        self.write_png(std::io::BufWriter::new(file))
+            .map_err(|e| format!("Unable to write {}: {}", path.display(), e))
+    }
+
+    /// Paints a single cell. Cells outside of the viewport are ignored.
+    pub fn set_cell(&mut self, cell: &Cell, is_alive: bool) {
+        let color = if is_alive {
+            self.alive
+        } else {
+            self.background
+        };
+        self.fill_cell(cell, color);
+    }
+
+    /// Paints a single cell with an arbitrary colour. Cells outside of the viewport are ignored.
+    pub fn fill_cell(&mut self, cell: &Cell, color: Rgba) {
+        if !self.viewport.contains(cell) {
+            return;
+        }
+        let width = self.width() as usize;
+        let scale = self.scale as usize;
+        let left = (cell.x - self.viewport.x) as usize * scale;
+        let top = (cell.y - self.viewport.y) as usize * scale;
+        for y in top..top + scale {
+            let row_start = (y * width + left) * 4;
+            for pixel in self.pixels[row_start..row_start + scale * 4].chunks_exact_mut(4) {
+                pixel.copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BACKGROUND: Rgba = [255, 255, 255, 255];
+    const ALIVE: Rgba = [0, 0, 0, 255];
+
+    fn frame(viewport: Viewport, scale: u32) -> Frame {
+        Frame::new(viewport, scale, BACKGROUND, ALIVE).unwrap()
+    }
+
+    fn engine_with(cols: u32, rows: u32, cells: &[(u32, u32)]) -> LifeEngine {
+        let mut engine = LifeEngine::new(cols, rows);
+        for (x, y) in cells {
+            engine.activate_cell(*x, *y).unwrap();
+        }
+        engine
+    }
+
+    #[test]
+    fn parse_color_reads_rgb_and_rgba() {
+        assert_eq!(parse_color("#ff8000"), Ok([255, 128, 0, 255]));
+        assert_eq!(parse_color("#ff800040"), Ok([255, 128, 0, 64]));
+        assert!(parse_color("ff8000").is_err());
+        assert!(parse_color("#ff80").is_err());
+        assert!(parse_color("#gg8000").is_err());
+    }
+
+    #[test]
+    fn new_frame_is_filled_with_background() {
+        let frame = frame(Region::new(0, 0, 3, 2), 1);
+        assert_eq!((frame.width(), frame.height()), (3, 2));
+        assert_eq!(frame.pixels().len(), 3 * 2 * 4);
+        assert!(frame.pixels().chunks_exact(4).all(|p| p == BACKGROUND));
+    }
+
+    #[test]
+    fn alive_cells_are_drawn_at_their_offset() {
+        let mut frame = frame(Region::new(0, 0, 3, 2), 1);
+        frame.draw_cells(&[Cell::new(1, 1)]);
+        let offset = (3 + 1) * 4;
+        assert_eq!(frame.pixels()[offset..offset + 4], ALIVE);
+        for (x, y) in [(0, 0), (1, 0), (2, 0), (0, 1), (2, 1)] {
+            assert_eq!(frame.get_pixel(x, y), BACKGROUND, "pixel {},{}", x, y);
+        }
+    }
+
+    #[test]
+    fn cells_are_scaled_into_squares() {
+        let mut frame = frame(Region::new(0, 0, 2, 2), 3);
+        assert_eq!((frame.width(), frame.height()), (6, 6));
+        frame.draw_cells(&[Cell::new(1, 0)]);
+        for y in 0..6 {
+            for x in 0..6 {
+                let expected = if (3..6).contains(&x) && y < 3 {
+                    ALIVE
+                } else {
+                    BACKGROUND
+                };
+                assert_eq!(frame.get_pixel(x, y), expected, "pixel {},{}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn cells_outside_of_the_viewport_are_clipped() {
+        let mut frame = frame(Region::new(10, 10, 4, 4), 1);
+        frame.draw_cells(&[
+            Cell::new(9, 10),
+            Cell::new(14, 10),
+            Cell::new(10, 14),
+            Cell::new(11, 10),
+            Cell::new(13, 13),
+        ]);
+        let alive: Vec<(u32, u32)> = (0..4)
+            .flat_map(|y| (0..4).map(move |x| (x, y)))
+            .filter(|(x, y)| frame.get_pixel(*x, *y) == ALIVE)
+            .collect();
+        assert_eq!(alive, [(1, 0), (3, 3)]);
+    }
+
+    #[test]
+    fn dead_cells_are_painted_back_to_background() {
+        let mut frame = frame(Region::new(0, 0, 2, 1), 2);
+        frame.draw_cells(&[Cell::new(0, 0), Cell::new(1, 0)]);
+        frame.set_cell(&Cell::new(0, 0), false);
+        assert_eq!(frame.get_pixel(1, 1), BACKGROUND);
+        assert_eq!(frame.get_pixel(2, 1), ALIVE);
+    }
+
+    #[test]
+    fn last_step_matches_a_full_redraw() {
+        // A glider heading into the clipped edge of the viewport and a blinker inside it.
+        let mut engine = engine_with(
+            20,
+            20,
+            &[
+                (3, 2),
+                (4, 3),
+                (2, 4),
+                (3, 4),
+                (4, 4),
+                (14, 6),
+                (14, 7),
+                (14, 8),
+            ],
+        );
+        let viewport = Region::new(2, 2, 10, 10);
+        let mut incremental = frame(viewport, 2);
+        incremental.draw_engine(&engine);
+        for generation in 1..=30 {
+            engine.next();
+            incremental.apply_last_step(&engine);
+            let mut full = frame(viewport, 2);
+            full.draw_engine(&engine);
+            assert_eq!(incremental, full, "generation {}", generation);
+        }
+    }
+
+    #[test]
+    fn oversized_frames_are_rejected() {
+        assert!(Frame::new(Region::new(0, 0, 70000, 1), 70000, BACKGROUND, ALIVE).is_err());
+        assert!(Frame::pixel_size(&Region::new(0, 0, u32::MAX, 1), 2).is_err());
+        assert_eq!(Frame::pixel_size(&Region::new(0, 0, 3, 2), 0), Ok((3, 2)));
+    }
+}
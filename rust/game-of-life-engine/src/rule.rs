@@ -0,0 +1,100 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// An outer totalistic rule: the neighbour counts at which a dead cell is born
+/// and at which an alive cell survives, stored as bit masks over 0..=8.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub struct Rule {
+    birth: u16,
+    survival: u16,
+}
+
+impl Rule {
+    /// Conway's Game of Life, B3/S23.
+    pub const CONWAY: Rule = Rule {
+        birth: 1 << 3,
+        survival: (1 << 2) | (1 << 3),
+    };
+
+    pub fn new(birth: &[u8], survival: &[u8]) -> Result<Rule, String> {
+        if birth.contains(&0) {
+            return Err(String::from(
+                "rules with birth on 0 neighbours are not supported",
+            ));
+        }
+        Ok(Rule {
+            birth: Self::to_mask(birth)?,
+            survival: Self::to_mask(survival)?,
+        })
+    }
+
+    pub fn is_born(&self, alive_neighbours_count: usize) -> bool {
+        self.birth & (1 << alive_neighbours_count) != 0
+    }
+
+    pub fn survives(&self, alive_neighbours_count: usize) -> bool {
+        self.survival & (1 << alive_neighbours_count) != 0
+    }
+
+    fn to_mask(counts: &[u8]) -> Result<u16, String> {
+        counts.iter().try_fold(0u16, |mask, &count| {
+            if count > 8 {
+                Err(format!("invalid neighbour count {} in rule", count))
+            } else {
+                Ok(mask | (1 << count))
+            }
+        })
+    }
+
+    fn parse_counts(digits: &str) -> Result<Vec<u8>, String> {
+        digits
+            .chars()
+            .map(|c| {
+                c.to_digit(10)
+                    .map(|d| d as u8)
+                    .ok_or_else(|| format!("invalid neighbour count '{}' in rule", c))
+            })
+            .collect()
+    }
+
+    fn format_counts(mask: u16, f: &mut Formatter<'_>) -> std::fmt::Result {
+        (0..=8)
+            .filter(|count| mask & (1 << count) != 0)
+            .try_for_each(|count| write!(f, "{}", count))
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::CONWAY
+    }
+}
+
+/// Parses rulestrings in the `B3/S23` notation, or in the legacy `23/3` survival/birth notation.
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (first, second) = s
+            .trim()
+            .split_once('/')
+            .ok_or_else(|| format!("invalid rule '{}', expected a rule like B3/S23", s))?;
+        let first_prefix = first.chars().next().map(|c| c.to_ascii_uppercase());
+        let second_prefix = second.chars().next().map(|c| c.to_ascii_uppercase());
+        let (birth, survival) = match (first_prefix, second_prefix) {
+            (Some('B'), Some('S')) => (&first[1..], &second[1..]),
+            (Some('S'), Some('B')) => (&second[1..], &first[1..]),
+            _ => (second, first),
+        };
+        Rule::new(&Self::parse_counts(birth)?, &Self::parse_counts(survival)?)
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "B")?;
+        Self::format_counts(self.birth, f)?;
+        write!(f, "/S")?;
+        Self::format_counts(self.survival, f)
+    }
+}
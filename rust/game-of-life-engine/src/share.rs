@@ -0,0 +1,415 @@
+use crate::{Cell, LifeEngine, Rule, Topology};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use miniz_oxide::deflate::compress_to_vec;
+use miniz_oxide::inflate::decompress_to_vec_with_limit;
+
+/// Version of the packed layout, written first so that older links can still be read
+/// once the layout changes.
+const VERSION: u8 = 1;
+/// Cap on the size of a decompressed state, so a crafted string can't exhaust memory.
+const MAX_PACKED_BYTES: usize = 64 * 1024 * 1024;
+/// Largest number of columns or rows of an encoded or decoded grid, so that a crafted string
+/// can't make its consumers size buffers or canvases after a huge world.
+pub const MAX_GRID_SIDE: u32 = 1 << 16;
+
+const CELLS_BITMAP: u8 = 0;
+const CELLS_OFFSETS: u8 = 1;
+
+/// Encodes the grid size, topology, rule, generation and alive cells of `engine` into a
+/// compact string that can be used as is in a URL, e.g. as its fragment.
+///
+/// The state is bit-packed, deflated and written as unpadded base64url. Cells are packed
+/// relative to their bounding box, either as a bitmap of it or, for sparse patterns, as
+/// the distances between consecutive cells in row-major order, whichever is smaller.
+///
+/// Grids with more than `MAX_GRID_SIDE` columns or rows are refused, as `decode` would
+/// reject them.
+pub fn encode(engine: &LifeEngine) -> Result<String, String> {
+    check_grid_size(engine.get_cols(), engine.get_rows())?;
+    Ok(URL_SAFE_NO_PAD.encode(compress_to_vec(&pack(engine), 10)))
+}
+
+/// Rebuilds an engine from a string written by `encode`. Grids with more than
+/// `MAX_GRID_SIDE` columns or rows are rejected.
+pub fn decode(text: &str) -> Result<LifeEngine, String> {
+    let compressed = URL_SAFE_NO_PAD
+        .decode(text.trim())
+        .map_err(|e| format!("invalid state: {}", e))?;
+    let packed = decompress_to_vec_with_limit(&compressed, MAX_PACKED_BYTES)
+        .map_err(|e| format!("invalid state: {}", e))?;
+    unpack(&packed)
+}
+
+fn pack(engine: &LifeEngine) -> Vec<u8> {
+    let mut packed = vec![VERSION];
+    write_varint(&mut packed, engine.get_cols() as u64);
+    write_varint(&mut packed, engine.get_rows() as u64);
+    packed.push(match engine.get_topology() {
+        Topology::Bounded => 0,
+        Topology::Torus => 1,
+    });
+    let rule = engine.get_rule();
+    // Births in the low 9 bits, survivals in the next 9, one bit per neighbour count.
+    let masks = (0..=8)
+        .filter(|count| rule.is_born(*count))
+        .fold(0, |mask, count| mask | 1 << count)
+        | (0..=8)
+            .filter(|count| rule.survives(*count))
+            .fold(0, |mask, count| mask | 1 << (count + 9));
+    write_varint(&mut packed, masks);
+    write_varint(&mut packed, engine.get_generation());
+    packed.extend(pack_cells(engine));
+    packed
+}
+
+fn unpack(packed: &[u8]) -> Result<LifeEngine, String> {
+    let mut reader = Reader { bytes: packed };
+    let version = reader.byte()?;
+    if version != VERSION {
+        return Err(format!("unsupported state version {}", version));
+    }
+    let cols = reader.u32()?;
+    let rows = reader.u32()?;
+    check_grid_size(cols, rows).map_err(|e| format!("invalid state: {}", e))?;
+    let topology = match reader.byte()? {
+        0 => Topology::Bounded,
+        1 => Topology::Torus,
+        value => return Err(format!("invalid state: unknown topology {}", value)),
+    };
+    let masks = reader.varint()?;
+    let counts = |shift: u8| {
+        (0..=8u8)
+            .filter(|count| masks >> (count + shift) & 1 != 0)
+            .collect::<Vec<u8>>()
+    };
+    let rule = Rule::new(&counts(0), &counts(9))?;
+    let generation = reader.varint()?;
+    let cells = unpack_cells(&mut reader, cols, rows)?;
+
+    let mut engine = LifeEngine::new(cols, rows);
+    engine.set_rule(rule);
+    engine.set_topology(topology);
+    engine.restore(generation, &cells);
+    Ok(engine)
+}
+
+fn check_grid_size(cols: u32, rows: u32) -> Result<(), String> {
+    if !(1..=MAX_GRID_SIDE).contains(&cols) || !(1..=MAX_GRID_SIDE).contains(&rows) {
+        return Err(format!(
+            "a {}×{} grid, grids have 1 to {} columns and rows",
+            cols, rows, MAX_GRID_SIDE
+        ));
+    }
+    Ok(())
+}
+
+fn pack_cells(engine: &LifeEngine) -> Vec<u8> {
+    let mut packed = Vec::new();
+    let Some((top_left, bottom_right)) = engine.get_bounding_box() else {
+        write_varint(&mut packed, 0);
+        return packed;
+    };
+    let width = (bottom_right.x - top_left.x + 1) as u64;
+    let height = (bottom_right.y - top_left.y + 1) as u64;
+    let mut offsets: Vec<u64> = engine
+        .get_alive_cells()
+        .map(|c| (c.y - top_left.y) as u64 * width + (c.x - top_left.x) as u64)
+        .collect();
+    offsets.sort_unstable();
+
+    write_varint(&mut packed, offsets.len() as u64);
+    write_varint(&mut packed, top_left.x as u64);
+    write_varint(&mut packed, top_left.y as u64);
+    write_varint(&mut packed, width);
+    write_varint(&mut packed, height);
+
+    let mut deltas = Vec::new();
+    let mut previous = 0;
+    for offset in &offsets {
+        write_varint(&mut deltas, offset - previous);
+        previous = *offset;
+    }
+    let bitmap_len = (width * height).div_ceil(8);
+    if bitmap_len <= deltas.len() as u64 {
+        let mut bitmap = vec![0; bitmap_len as usize];
+        for offset in offsets {
+            bitmap[(offset / 8) as usize] |= 1 << (offset % 8);
+        }
+        packed.push(CELLS_BITMAP);
+        packed.extend(bitmap);
+    } else {
+        packed.push(CELLS_OFFSETS);
+        packed.extend(deltas);
+    }
+    packed
+}
+
+fn unpack_cells(reader: &mut Reader, cols: u32, rows: u32) -> Result<Vec<Cell>, String> {
+    let count = reader.varint()?;
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+    let left = reader.u32()?;
+    let top = reader.u32()?;
+    let width = reader.u32()? as u64;
+    let height = reader.u32()? as u64;
+    if width == 0
+        || height == 0
+        || left as u64 + width > cols as u64
+        || top as u64 + height > rows as u64
+    {
+        return Err(String::from("invalid state: cells outside of the grid"));
+    }
+    if count > width * height {
+        return Err(String::from("invalid state: too many cells"));
+    }
+    let cell_at = |offset: u64| {
+        Cell::new(
+            left + (offset % width) as u32,
+            top + (offset / width) as u32,
+        )
+    };
+
+    // The count is only trusted as far as the remaining bytes could hold that many cells.
+    let mut cells = Vec::with_capacity(count.min(reader.bytes.len() as u64 * 8) as usize);
+    match reader.byte()? {
+        CELLS_BITMAP => {
+            let bitmap_len = usize::try_from((width * height).div_ceil(8))
+                .map_err(|_| String::from("invalid state: truncated"))?;
+            let bitmap = reader.take(bitmap_len)?;
+            for (index, byte) in bitmap.iter().enumerate().filter(|(_, byte)| **byte != 0) {
+                for bit in (0..8).filter(|bit| byte & 1 << bit != 0) {
+                    let offset = index as u64 * 8 + bit;
+                    if offset >= width * height {
+                        return Err(String::from("invalid state: cells outside of the grid"));
+                    }
+                    cells.push(cell_at(offset));
+                }
+            }
+            if cells.len() as u64 != count {
+                return Err(String::from("invalid state: cell count mismatch"));
+            }
+        }
+        CELLS_OFFSETS => {
+            let mut offset = 0u64;
+            for i in 0..count {
+                let delta = reader.varint()?;
+                if i > 0 && delta == 0 {
+                    return Err(String::from("invalid state: duplicate cell"));
+                }
+                offset = offset
+                    .checked_add(delta)
+                    .filter(|offset| *offset < width * height)
+                    .ok_or("invalid state: cells outside of the grid")?;
+                cells.push(cell_at(offset));
+            }
+        }
+        value => return Err(format!("invalid state: unknown cell packing {}", value)),
+    }
+    Ok(cells)
+}
+
+/// Writes `value` as a LEB128 varint: 7 bits per byte, low bits first, with the high bit
+/// set on every byte but the last.
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() < count {
+            return Err(String::from("invalid state: truncated"));
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            if shift == 63 && byte > 1 {
+                return Err(String::from("invalid state: varint too long"));
+            }
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(String::from("invalid state: varint too long"))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        u32::try_from(self.varint()?).map_err(|_| String::from("invalid state: value too large"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine_with(cols: u32, rows: u32, cells: &[(u32, u32)]) -> LifeEngine {
+        let mut engine = LifeEngine::new(cols, rows);
+        for (x, y) in cells {
+            engine.activate_cell(*x, *y).unwrap();
+        }
+        engine
+    }
+
+    fn sorted_cells(engine: &LifeEngine) -> Vec<Cell> {
+        let mut cells: Vec<Cell> = engine.get_alive_cells().cloned().collect();
+        cells.sort();
+        cells
+    }
+
+    fn assert_round_trip(engine: &LifeEngine) {
+        let decoded = decode(&encode(engine).unwrap()).unwrap();
+        assert_eq!(decoded.get_cols(), engine.get_cols());
+        assert_eq!(decoded.get_rows(), engine.get_rows());
+        assert_eq!(decoded.get_rule(), engine.get_rule());
+        assert_eq!(decoded.get_topology(), engine.get_topology());
+        assert_eq!(decoded.get_generation(), engine.get_generation());
+        assert_eq!(sorted_cells(&decoded), sorted_cells(engine));
+    }
+
+    // Packing mode of a world whose cell header values all fit into single byte varints.
+    fn packing(engine: &LifeEngine) -> u8 {
+        pack_cells(engine)[5]
+    }
+
+    // Packed header of a Conway world at generation 0, followed by `cells`.
+    fn packed(version: u8, cols: u64, rows: u64, cells: &[u64]) -> Vec<u8> {
+        let mut bytes = vec![version];
+        write_varint(&mut bytes, cols);
+        write_varint(&mut bytes, rows);
+        bytes.push(0);
+        write_varint(&mut bytes, 1 << 3 | 1 << (2 + 9) | 1 << (3 + 9));
+        write_varint(&mut bytes, 0);
+        for value in cells {
+            write_varint(&mut bytes, *value);
+        }
+        bytes
+    }
+
+    #[test]
+    fn empty_world_round_trips() {
+        assert_round_trip(&LifeEngine::new(80, 60));
+    }
+
+    #[test]
+    fn dense_world_round_trips_as_bitmap() {
+        let cells: Vec<(u32, u32)> = (0..64)
+            .filter(|i| i % 3 != 0)
+            .map(|i| (10 + i % 8, 20 + i / 8))
+            .collect();
+        let engine = engine_with(80, 60, &cells);
+        assert_eq!(packing(&engine), CELLS_BITMAP);
+        assert_round_trip(&engine);
+    }
+
+    #[test]
+    fn sparse_world_round_trips_as_offsets() {
+        let engine = engine_with(100, 100, &[(1, 2), (50, 3), (99, 99)]);
+        assert_eq!(packing(&engine), CELLS_OFFSETS);
+        assert_round_trip(&engine);
+        assert_round_trip(&engine_with(
+            MAX_GRID_SIDE,
+            MAX_GRID_SIDE,
+            &[(0, 0), (MAX_GRID_SIDE - 1, MAX_GRID_SIDE - 1)],
+        ));
+    }
+
+    #[test]
+    fn rule_topology_and_generation_round_trip() {
+        let mut engine = engine_with(40, 30, &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        engine.set_rule("B36/S23".parse().unwrap());
+        engine.set_topology(Topology::Torus);
+        for _ in 0..7 {
+            engine.next();
+        }
+        assert_round_trip(&engine);
+
+        let mut decoded = decode(&encode(&engine).unwrap()).unwrap();
+        engine.next();
+        decoded.next();
+        assert_eq!(sorted_cells(&decoded), sorted_cells(&engine));
+    }
+
+    #[test]
+    fn truncated_states_are_rejected() {
+        let engine = engine_with(80, 60, &[(1, 2), (50, 3)]);
+        let bytes = pack(&engine);
+        for length in 0..bytes.len() {
+            assert!(unpack(&bytes[..length]).is_err(), "length {}", length);
+        }
+        let text = encode(&engine).unwrap();
+        assert!(decode(&text[..text.len() / 2]).is_err());
+        assert!(decode("not a state!").is_err());
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        let mut bytes = pack(&LifeEngine::new(10, 10));
+        bytes[0] = VERSION + 1;
+        assert!(unpack(&bytes).unwrap_err().contains("version"));
+    }
+
+    #[test]
+    fn duplicate_cells_are_rejected() {
+        // Two cells in a 2×1 box at (0, 0), both at offset 0.
+        let bytes = packed(
+            VERSION,
+            10,
+            10,
+            &[2, 0, 0, 2, 1, CELLS_OFFSETS as u64, 0, 0],
+        );
+        assert!(unpack(&bytes).unwrap_err().contains("duplicate"));
+    }
+
+    #[test]
+    fn bounding_boxes_outside_of_the_grid_are_rejected() {
+        // A 5×1 box starting at column 8 of a 10×10 grid.
+        let bytes = packed(VERSION, 10, 10, &[1, 8, 0, 5, 1, CELLS_OFFSETS as u64, 0]);
+        assert!(unpack(&bytes).unwrap_err().contains("outside"));
+        // An offset past the end of a 2×2 box.
+        let bytes = packed(VERSION, 10, 10, &[1, 0, 0, 2, 2, CELLS_OFFSETS as u64, 4]);
+        assert!(unpack(&bytes).unwrap_err().contains("outside"));
+    }
+
+    #[test]
+    fn oversized_and_empty_grids_are_rejected() {
+        let too_wide = packed(VERSION, MAX_GRID_SIDE as u64 + 1, 10, &[0]);
+        assert!(unpack(&too_wide).is_err());
+        let huge = packed(VERSION, u32::MAX as u64, u32::MAX as u64, &[0]);
+        assert!(decode(&URL_SAFE_NO_PAD.encode(compress_to_vec(&huge, 10))).is_err());
+        assert!(unpack(&packed(VERSION, 0, 10, &[0])).is_err());
+        assert!(unpack(&packed(VERSION, 10, 10, &[0])).is_ok());
+    }
+
+    #[test]
+    fn grid_size_limit_holds_for_encoding_and_decoding() {
+        let largest = engine_with(MAX_GRID_SIDE, MAX_GRID_SIDE, &[(0, 0)]);
+        assert!(decode(&encode(&largest).unwrap()).is_ok());
+        for (cols, rows) in [(MAX_GRID_SIDE + 1, 10), (10, MAX_GRID_SIDE + 1)] {
+            let error = encode(&LifeEngine::new(cols, rows)).unwrap_err();
+            assert!(error.contains(&MAX_GRID_SIDE.to_string()), "{}", error);
+            let bytes = packed(VERSION, cols as u64, rows as u64, &[0]);
+            let text = URL_SAFE_NO_PAD.encode(compress_to_vec(&bytes, 10));
+            assert!(decode(&text).unwrap_err().contains("invalid state"));
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// What lies beyond the edges of the grid.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, Default)]
+pub enum Topology {
+    /// Cells outside of the grid are always dead.
+    #[default]
+    Bounded,
+    /// Opposite edges are joined: cells on the right edge neighbour those on the left edge,
+    /// and cells on the bottom edge neighbour those on the top edge.
+    Torus,
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "bounded" => Ok(Topology::Bounded),
+            "torus" => Ok(Topology::Torus),
+            _ => Err(format!(
+                "invalid topology '{}', expected bounded or torus",
+                s
+            )),
+        }
+    }
+}
+
+impl Display for Topology {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Topology::Bounded => write!(f, "bounded"),
+            Topology::Torus => write!(f, "torus"),
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use std::path::Path;
+
+// Generates the C header into OUT_DIR whenever the exported functions change. The copy under
+// include/ is checked in and compared with it by the header_is_up_to_date test, so builds
+// never write into the source tree.
+fn main() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    let header = Path::new(&out_dir).join("game_of_life_ffi.h");
+    match cbindgen::generate(&crate_dir) {
+        Ok(bindings) => {
+            bindings.write_to_file(&header);
+        }
+        Err(e) => {
+            // The library itself doesn't need the header, only the test comparing it does.
+            println!("cargo:warning=Unable to generate the C header: {}", e);
+            std::fs::write(&header, "").unwrap();
+        }
+    }
+}
@@ -0,0 +1,240 @@
+/* Generated by cbindgen from src/lib.rs. Do not edit: run `UPDATE_FFI_HEADER=1 cargo test` to update it. */
+
+#ifndef GAME_OF_LIFE_FFI_H
+#define GAME_OF_LIFE_FFI_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Version of the C ABI, bumped whenever a function changes in an incompatible way.
+#define ENGINE_ABI_VERSION 1
+
+// Capability flag: engine_set_topology supports ENGINE_TOPOLOGY_TORUS.
+#define ENGINE_CAPABILITY_TORUS (1 << 0)
+
+// Capability flag: undo, redo and stepping back with engine_set_history.
+#define ENGINE_CAPABILITY_HISTORY (1 << 1)
+
+// Capability flag: engine_load_rle and engine_save_rle.
+#define ENGINE_CAPABILITY_RLE (1 << 2)
+
+// Capability flag: engine_set_step_observer.
+#define ENGINE_CAPABILITY_STEP_OBSERVER (1 << 3)
+
+// Cells outside of the grid are always dead.
+#define ENGINE_TOPOLOGY_BOUNDED 0
+
+// Opposite edges of the grid are joined.
+#define ENGINE_TOPOLOGY_TORUS 1
+
+// Outcome of an FFI call. The details of a failure are kept for engine_last_error_message.
+typedef enum EngineStatus {
+  EngineStatus_Ok = 0,
+  // A required pointer argument was null.
+  EngineStatus_NullPointer = 1,
+  // An argument was out of range, e.g. a cell outside of the grid or a length that overflows.
+  EngineStatus_InvalidArgument = 2,
+  // The engine panicked. It may have been left half updated and should be freed.
+  EngineStatus_Panic = 3,
+} EngineStatus;
+
+// Opaque iterator created by engine_alive_cells_iterator_get.
+typedef struct CellsIterator CellsIterator;
+
+// Opaque engine created by engine_new.
+typedef struct EngineHandle EngineHandle;
+
+// Called by engine_step after each step with the `user_data` it was registered with, the new
+// generation and the number of alive cells. Returning non-zero stops the run.
+typedef int32_t (*EngineStepObserver)(void *user_data, uint64_t generation, size_t population);
+
+typedef struct Cell {
+  uint32_t x;
+  uint32_t y;
+} Cell;
+
+// A rectangle of cells, given by its top left cell and its size in cells.
+typedef struct Region {
+  uint32_t x;
+  uint32_t y;
+  uint32_t width;
+  uint32_t height;
+} Region;
+
+
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Get the ABI version and the capability flags of this library, so that callers can check
+// that it provides what they need before using it.
+enum EngineStatus engine_get_version(uint32_t *abi_version, uint32_t *capabilities);
+
+// Get the version of the library as a static, null terminated string such as "0.1.0".
+const char *engine_version_string(void);
+
+// Create a new Engine and write an opaque pointer to it into `engine`.
+enum EngineStatus engine_new(uint32_t cols, uint32_t rows, struct EngineHandle **engine);
+
+// Destroy an Engine previously created by engine_new. Freeing null does nothing.
+// Iterators created from the engine stay usable until they are freed themselves.
+enum EngineStatus engine_free(struct EngineHandle *ptr);
+
+// Get the size of the grid in cells.
+enum EngineStatus engine_get_size(const struct EngineHandle *ptr, uint32_t *cols, uint32_t *rows);
+
+// Set the rule from a UTF-8 rulestring of `length` bytes, such as "B36/S23" or "23/36".
+enum EngineStatus engine_set_rule(struct EngineHandle *ptr, const uint8_t *rule, size_t length);
+
+// Copy the rulestring, in the B3/S23 notation and without a null terminator, into `buffer`
+// and write its size in bytes into `length`. Nothing is copied when it is greater than
+// `capacity`; the call should then be repeated with a buffer of at least that size.
+enum EngineStatus engine_get_rule(const struct EngineHandle *ptr,
+                                  uint8_t *buffer,
+                                  size_t capacity,
+                                  size_t *length);
+
+// Set the topology to ENGINE_TOPOLOGY_BOUNDED or ENGINE_TOPOLOGY_TORUS.
+enum EngineStatus engine_set_topology(struct EngineHandle *ptr, uint32_t topology);
+
+// Get the topology, ENGINE_TOPOLOGY_BOUNDED or ENGINE_TOPOLOGY_TORUS.
+enum EngineStatus engine_get_topology(const struct EngineHandle *ptr, uint32_t *topology);
+
+// Advance the engine by one tick.
+enum EngineStatus engine_next(struct EngineHandle *ptr);
+
+// Register the observer engine_step calls after each step, replacing the previous one.
+// A null observer removes it. The observer may query and even modify the engine, but must
+// not free it.
+enum EngineStatus engine_set_step_observer(struct EngineHandle *ptr,
+                                           EngineStepObserver observer,
+                                           void *user_data);
+
+// Advance the engine by `steps` ticks, calling the step observer after each one. The run ends
+// early if the observer returns non-zero; engine_get_generation tells how far it went.
+enum EngineStatus engine_step(struct EngineHandle *ptr, uint64_t steps);
+
+// Get the number of ticks the engine was advanced by.
+enum EngineStatus engine_get_generation(const struct EngineHandle *ptr, uint64_t *generation);
+
+// Activate a cell at (x, y).
+enum EngineStatus engine_activate_cell(struct EngineHandle *ptr, uint32_t x, uint32_t y);
+
+// Activate a set of cells. `cells` may only be null when `count` is 0.
+enum EngineStatus engine_activate_cells(struct EngineHandle *ptr,
+                                        const struct Cell *cells,
+                                        size_t count);
+
+// Deactivate the cell at (x, y).
+enum EngineStatus engine_deactivate_cell(struct EngineHandle *ptr, uint32_t x, uint32_t y);
+
+// Write whether the cell at (x, y) is alive into `is_alive`.
+enum EngineStatus engine_is_cell_alive(const struct EngineHandle *ptr,
+                                       uint32_t x,
+                                       uint32_t y,
+                                       bool *is_alive);
+
+// Deactivate every cell.
+enum EngineStatus engine_clear(struct EngineHandle *ptr);
+
+// Deactivate the cells inside the rectangle of `width`×`height` cells whose top left cell is (x, y).
+enum EngineStatus engine_clear_rect(struct EngineHandle *ptr,
+                                    uint32_t x,
+                                    uint32_t y,
+                                    uint32_t width,
+                                    uint32_t height);
+
+// Generate a random square of cells.
+enum EngineStatus engine_generate_random_square(struct EngineHandle *ptr,
+                                                uint32_t top_left_x,
+                                                uint32_t top_left_y,
+                                                uint32_t size);
+
+// Get the number of alive cells.
+enum EngineStatus engine_get_population(const struct EngineHandle *ptr, size_t *population);
+
+// Write the smallest rectangle containing every alive cell into `bounding_box`, and whether
+// there are any alive cells into `has_cells`. The rectangle is left untouched when there are none.
+enum EngineStatus engine_get_bounding_box(const struct EngineHandle *ptr,
+                                          struct Region *bounding_box,
+                                          bool *has_cells);
+
+// Copy the alive cells into `buffer`, which has room for `capacity` cells, in no particular order,
+// and write the number of alive cells into `count`. When it is greater than `capacity` only the
+// first `capacity` cells were copied, and the call should be repeated with a buffer of at least
+// that size. Passing a null buffer and a capacity of 0 only queries the count.
+enum EngineStatus engine_copy_alive_cells(const struct EngineHandle *ptr,
+                                          struct Cell *buffer,
+                                          size_t capacity,
+                                          size_t *count);
+
+// Copy the alive cells inside the rectangle of `width`×`height` cells whose top left cell is
+// (x, y) into `buffer`. Follows the same count/required-size protocol as engine_copy_alive_cells.
+enum EngineStatus engine_copy_alive_cells_in_rect(const struct EngineHandle *ptr,
+                                                  uint32_t x,
+                                                  uint32_t y,
+                                                  uint32_t width,
+                                                  uint32_t height,
+                                                  struct Cell *buffer,
+                                                  size_t capacity,
+                                                  size_t *count);
+
+// Produce an iterator over a snapshot of the alive cells and write it into `iterator`.
+// The iterator owns its copy of the cells: it goes on returning the cells alive when it was
+// created, even once the engine is modified or freed.
+enum EngineStatus engine_alive_cells_iterator_get(const struct EngineHandle *ptr,
+                                                  struct CellsIterator **iterator);
+
+// Destroy an iterator previously created by engine_alive_cells_iterator_get. Freeing null does nothing.
+enum EngineStatus engine_alive_cells_iterator_free(struct CellsIterator *ptr);
+
+// Copy the next alive cell from the iterator into `cell` and write whether there was one into
+// `has_cell`, which is false once all cells were seen.
+enum EngineStatus engine_alive_cells_iterator_next(struct CellsIterator *ptr,
+                                                   struct Cell *cell,
+                                                   bool *has_cell);
+
+// Enable or disable the history. While it is enabled, edits of the cells can be undone and
+// redone, and the engine can step back to earlier generations. Disabling it forgets everything.
+enum EngineStatus engine_set_history(struct EngineHandle *ptr, bool is_enabled);
+
+// Restore the cells from before the last edit, writing whether there was one to undo into `done`.
+enum EngineStatus engine_undo(struct EngineHandle *ptr, bool *done);
+
+// Restore the cells undone last, writing whether there was something to redo into `done`.
+enum EngineStatus engine_redo(struct EngineHandle *ptr, bool *done);
+
+// Go back to the previous generation, writing whether it was still known into `done`.
+enum EngineStatus engine_step_back(struct EngineHandle *ptr, bool *done);
+
+// Activate the cells of an RLE pattern, given as `length` bytes of UTF-8 text, with its top
+// left corner at (x, y). Cells falling outside of the grid are dropped. If the pattern
+// specifies a rule, the engine switches to it. Like engine_set_rule, the rule change isn't
+// recorded in the history: engine_undo removes the pattern's cells but keeps the new rule.
+enum EngineStatus engine_load_rle(struct EngineHandle *ptr,
+                                  const uint8_t *data,
+                                  size_t length,
+                                  uint32_t x,
+                                  uint32_t y);
+
+// Copy the alive cells as an RLE pattern with the engine's rule into `buffer`, and write its
+// size in bytes into `length`. The pattern starts at the top left corner of the bounding box
+// given by engine_get_bounding_box. Follows the same protocol as engine_get_rule.
+enum EngineStatus engine_save_rle(const struct EngineHandle *ptr,
+                                  uint8_t *buffer,
+                                  size_t capacity,
+                                  size_t *length);
+
+// Get the message of the last call that failed on the calling thread, or null if none has.
+// The string is UTF-8 and stays valid until the next failing call on the same thread.
+const char *engine_last_error_message(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* GAME_OF_LIFE_FFI_H */
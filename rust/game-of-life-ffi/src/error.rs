@@ -0,0 +1,76 @@
+use std::cell::RefCell;
+use std::ffi::{CString, c_char};
+use std::panic::{self, AssertUnwindSafe};
+
+/// Outcome of an FFI call. The details of a failure are kept for engine_last_error_message.
+#[repr(C)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum EngineStatus {
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullPointer = 1,
+    /// An argument was out of range, e.g. a cell outside of the grid or a length that overflows.
+    InvalidArgument = 2,
+    /// The engine panicked. It may have been left half updated and should be freed.
+    Panic = 3,
+}
+
+pub(crate) struct Failure {
+    status: EngineStatus,
+    message: String,
+}
+
+impl Failure {
+    pub(crate) fn null_pointer(argument: &str) -> Failure {
+        Failure {
+            status: EngineStatus::NullPointer,
+            message: format!("{} must not be null", argument),
+        }
+    }
+
+    pub(crate) fn invalid_argument(message: impl Into<String>) -> Failure {
+        Failure {
+            status: EngineStatus::InvalidArgument,
+            message: message.into(),
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+// Run the body of an FFI call, turning failures and panics into a status and keeping their
+// message, so that no panic unwinds into the caller.
+pub(crate) fn guard(body: impl FnOnce() -> Result<(), Failure>) -> EngineStatus {
+    let failure = match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => return EngineStatus::Ok,
+        Ok(Err(failure)) => failure,
+        Err(payload) => Failure {
+            status: EngineStatus::Panic,
+            message: payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .map_or_else(
+                    || "Engine panicked".to_string(),
+                    |s| format!("Engine panicked: {}", s),
+                ),
+        },
+    };
+    let message = CString::new(failure.message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+    failure.status
+}
+
+/// Get the message of the last call that failed on the calling thread, or null if none has.
+/// The string is UTF-8 and stays valid until the next failing call on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last_error| {
+        last_error
+            .borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
@@ -0,0 +1,103 @@
+use crate::EngineStepObserver;
+use game_of_life_engine::history::History;
+use game_of_life_engine::{Cell, LifeEngine};
+use std::ffi::c_void;
+
+/// Opaque engine created by engine_new.
+// Holds the engine and its optional history, which records the edits made through `edit`.
+pub struct EngineHandle {
+    engine: LifeEngine,
+    history: Option<History>,
+    step_observer: EngineStepObserver,
+    step_observer_data: *mut c_void,
+}
+
+impl EngineHandle {
+    pub(crate) fn new(engine: LifeEngine) -> EngineHandle {
+        EngineHandle {
+            engine,
+            history: None,
+            step_observer: None,
+            step_observer_data: std::ptr::null_mut(),
+        }
+    }
+
+    pub(crate) fn engine(&self) -> &LifeEngine {
+        &self.engine
+    }
+
+    // Borrow the engine for a change the history does not record, e.g. of the rule.
+    pub(crate) fn engine_mut(&mut self) -> &mut LifeEngine {
+        &mut self.engine
+    }
+
+    // Apply an edit of the cells, recorded so that it can be undone when the history is enabled.
+    pub(crate) fn edit<T>(&mut self, edit: impl FnOnce(&mut LifeEngine) -> T) -> T {
+        match self.history.as_mut() {
+            Some(history) => history.edit(&mut self.engine, edit),
+            None => edit(&mut self.engine),
+        }
+    }
+
+    pub(crate) fn step(&mut self) {
+        match self.history.as_mut() {
+            Some(history) => history.step(&mut self.engine),
+            None => self.engine.next(),
+        }
+    }
+
+    pub(crate) fn set_history(&mut self, is_enabled: bool) {
+        if is_enabled != self.history.is_some() {
+            self.history = is_enabled.then(History::new);
+        }
+    }
+
+    pub(crate) fn set_step_observer(
+        &mut self,
+        observer: EngineStepObserver,
+        user_data: *mut c_void,
+    ) {
+        self.step_observer = observer;
+        self.step_observer_data = user_data;
+    }
+
+    pub(crate) fn step_observer(&self) -> (EngineStepObserver, *mut c_void) {
+        (self.step_observer, self.step_observer_data)
+    }
+
+    // Run a history action such as undo. Returns false if the history is disabled
+    // or the action had nothing to do.
+    pub(crate) fn travel(
+        &mut self,
+        action: impl FnOnce(&mut History, &mut LifeEngine) -> bool,
+    ) -> bool {
+        match self.history.as_mut() {
+            Some(history) => action(history, &mut self.engine),
+            None => false,
+        }
+    }
+}
+
+/// Opaque iterator created by engine_alive_cells_iterator_get.
+// Iterates a snapshot of the alive cells. It owns its cells, so it is unaffected by changes
+// to the engine and never reads freed memory.
+pub struct CellsIterator {
+    cells: std::vec::IntoIter<Cell>,
+}
+
+impl CellsIterator {
+    pub(crate) fn new(handle: &EngineHandle) -> CellsIterator {
+        CellsIterator {
+            cells: handle
+                .engine
+                .get_alive_cells()
+                .cloned()
+                .collect::<Vec<_>>()
+                .into_iter(),
+        }
+    }
+
+    pub(crate) fn next(&mut self) -> Option<Cell> {
+        self.cells.next()
+    }
+}
@@ -1,95 +1,706 @@
-use std::collections::hash_set::Iter;
-use std::collections::HashSet;
-use game_of_life_engine::{Cell, LifeEngine};
+mod error;
+mod handle;
+
+use error::{Failure, guard};
+use game_of_life_engine::format::{self, PatternDocument};
+use game_of_life_engine::{Cell, LifeEngine, Region, Rule, Topology};
+use std::ffi::{c_char, c_void};
+
+pub use error::{EngineStatus, engine_last_error_message};
+pub use handle::{CellsIterator, EngineHandle};
 
 /* ===== C-compatible FFI surface for C#/PInvoke ===== */
 
-// Create a new Engine and return an opaque pointer to it.
+// Every function but the version queries returns an EngineStatus. Results are written through
+// out pointers, and when a call fails, engine_last_error_message describes why. The C header
+// include/game_of_life_ffi.h is generated from this file by cbindgen, and the tests check that
+// the checked in copy is up to date.
+//
+// Pointers handed over by the caller are checked for null, but otherwise trusted to be valid.
+// The functions are only meant to be called through the C ABI, so they aren't marked unsafe,
+// and the few that dereference a pointer argument themselves rather than through the helpers
+// at the bottom allow clippy::not_unsafe_ptr_arg_deref.
+
+/// Version of the C ABI, bumped whenever a function changes in an incompatible way.
+pub const ENGINE_ABI_VERSION: u32 = 1;
+
+/// Capability flag: engine_set_topology supports ENGINE_TOPOLOGY_TORUS.
+pub const ENGINE_CAPABILITY_TORUS: u32 = 1 << 0;
+/// Capability flag: undo, redo and stepping back with engine_set_history.
+pub const ENGINE_CAPABILITY_HISTORY: u32 = 1 << 1;
+/// Capability flag: engine_load_rle and engine_save_rle.
+pub const ENGINE_CAPABILITY_RLE: u32 = 1 << 2;
+/// Capability flag: engine_set_step_observer.
+pub const ENGINE_CAPABILITY_STEP_OBSERVER: u32 = 1 << 3;
+
+/// Cells outside of the grid are always dead.
+pub const ENGINE_TOPOLOGY_BOUNDED: u32 = 0;
+/// Opposite edges of the grid are joined.
+pub const ENGINE_TOPOLOGY_TORUS: u32 = 1;
+
+/// Get the ABI version and the capability flags of this library, so that callers can check
+/// that it provides what they need before using it.
 #[unsafe(no_mangle)]
-pub extern "C" fn engine_new(cols: u32, rows: u32) -> *mut LifeEngine {
-    Box::into_raw(Box::new(LifeEngine::new(cols, rows)))
+pub extern "C" fn engine_get_version(
+    abi_version: *mut u32,
+    capabilities: *mut u32,
+) -> EngineStatus {
+    guard(|| {
+        write_out(abi_version, "abi_version", ENGINE_ABI_VERSION)?;
+        write_out(
+            capabilities,
+            "capabilities",
+            ENGINE_CAPABILITY_TORUS
+                | ENGINE_CAPABILITY_HISTORY
+                | ENGINE_CAPABILITY_RLE
+                | ENGINE_CAPABILITY_STEP_OBSERVER,
+        )
+    })
 }
 
-// Destroy an Engine previously created by engine_new.
+/// Get the version of the library as a static, null terminated string such as "0.1.0".
 #[unsafe(no_mangle)]
-pub extern "C" fn engine_free(ptr: *mut LifeEngine) {
-    if ptr.is_null() {
-        return;
-    }
-    unsafe {
-        drop(Box::from_raw(ptr));
-    }
+pub extern "C" fn engine_version_string() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
 }
 
-// Advance the engine by one tick.
+/* ----- Lifecycle ----- */
+
+/// Create a new Engine and write an opaque pointer to it into `engine`.
 #[unsafe(no_mangle)]
-pub extern "C" fn engine_next(ptr: *mut LifeEngine) {
-    if let Some(engine) = unsafe { ptr.as_mut() } {
-        engine.next();
-    }
+pub extern "C" fn engine_new(cols: u32, rows: u32, engine: *mut *mut EngineHandle) -> EngineStatus {
+    guard(|| {
+        let handle = EngineHandle::new(LifeEngine::new(cols, rows));
+        write_out(engine, "engine", Box::into_raw(Box::new(handle)))
+    })
 }
 
-// Activate a cell at (x, y).
+/// Destroy an Engine previously created by engine_new. Freeing null does nothing.
+/// Iterators created from the engine stay usable until they are freed themselves.
 #[unsafe(no_mangle)]
-pub extern "C" fn engine_activate_cell(ptr: *mut LifeEngine, x: u32, y: u32) {
-    if let Some(engine) = unsafe { ptr.as_mut() } {
-        let _ = engine.activate_cell(x, y);
-    }
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn engine_free(ptr: *mut EngineHandle) -> EngineStatus {
+    guard(|| {
+        if !ptr.is_null() {
+            drop(unsafe { Box::from_raw(ptr) });
+        }
+        Ok(())
+    })
 }
 
-// Activate a set of cells.
+/* ----- World settings ----- */
+
+/// Get the size of the grid in cells.
 #[unsafe(no_mangle)]
-pub extern "C" fn engine_activate_cells(ptr: *mut LifeEngine, cells: *const Cell, count: usize) {
-    if let Some(engine) = unsafe { ptr.as_mut() } {
-        let cells_slice = unsafe { std::slice::from_raw_parts(cells, count) };
-        let _ =engine.activate_cells(cells_slice);
-    }
+pub extern "C" fn engine_get_size(
+    ptr: *const EngineHandle,
+    cols: *mut u32,
+    rows: *mut u32,
+) -> EngineStatus {
+    guard(|| {
+        let engine = engine_ref(ptr)?;
+        write_out(cols, "cols", engine.get_cols())?;
+        write_out(rows, "rows", engine.get_rows())
+    })
+}
+
+/// Set the rule from a UTF-8 rulestring of `length` bytes, such as "B36/S23" or "23/36".
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_set_rule(
+    ptr: *mut EngineHandle,
+    rule: *const u8,
+    length: usize,
+) -> EngineStatus {
+    guard(|| {
+        let handle = handle_mut(ptr)?;
+        let rule = text(rule, length, "rule")?
+            .parse::<Rule>()
+            .map_err(Failure::invalid_argument)?;
+        handle.engine_mut().set_rule(rule);
+        Ok(())
+    })
+}
+
+/// Copy the rulestring, in the B3/S23 notation and without a null terminator, into `buffer`
+/// and write its size in bytes into `length`. Nothing is copied when it is greater than
+/// `capacity`; the call should then be repeated with a buffer of at least that size.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_get_rule(
+    ptr: *const EngineHandle,
+    buffer: *mut u8,
+    capacity: usize,
+    length: *mut usize,
+) -> EngineStatus {
+    guard(|| {
+        let rule = engine_ref(ptr)?.get_rule().to_string();
+        copy_bytes(rule.as_bytes(), buffer, capacity, length)
+    })
+}
+
+/// Set the topology to ENGINE_TOPOLOGY_BOUNDED or ENGINE_TOPOLOGY_TORUS.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_set_topology(ptr: *mut EngineHandle, topology: u32) -> EngineStatus {
+    guard(|| {
+        let handle = handle_mut(ptr)?;
+        let topology = match topology {
+            ENGINE_TOPOLOGY_BOUNDED => Topology::Bounded,
+            ENGINE_TOPOLOGY_TORUS => Topology::Torus,
+            _ => {
+                return Err(Failure::invalid_argument(format!(
+                    "unknown topology {}",
+                    topology
+                )));
+            }
+        };
+        handle.engine_mut().set_topology(topology);
+        Ok(())
+    })
+}
+
+/// Get the topology, ENGINE_TOPOLOGY_BOUNDED or ENGINE_TOPOLOGY_TORUS.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_get_topology(
+    ptr: *const EngineHandle,
+    topology: *mut u32,
+) -> EngineStatus {
+    guard(|| {
+        let value = match engine_ref(ptr)?.get_topology() {
+            Topology::Bounded => ENGINE_TOPOLOGY_BOUNDED,
+            Topology::Torus => ENGINE_TOPOLOGY_TORUS,
+        };
+        write_out(topology, "topology", value)
+    })
+}
+
+/* ----- Stepping ----- */
+
+/// Advance the engine by one tick.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_next(ptr: *mut EngineHandle) -> EngineStatus {
+    guard(|| {
+        handle_mut(ptr)?.step();
+        Ok(())
+    })
+}
+
+/// Called by engine_step after each step with the `user_data` it was registered with, the new
+/// generation and the number of alive cells. Returning non-zero stops the run.
+pub type EngineStepObserver =
+    Option<extern "C" fn(user_data: *mut c_void, generation: u64, population: usize) -> i32>;
+
+/// Register the observer engine_step calls after each step, replacing the previous one.
+/// A null observer removes it. The observer may query and even modify the engine, but must
+/// not free it.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_set_step_observer(
+    ptr: *mut EngineHandle,
+    observer: EngineStepObserver,
+    user_data: *mut c_void,
+) -> EngineStatus {
+    guard(|| {
+        handle_mut(ptr)?.set_step_observer(observer, user_data);
+        Ok(())
+    })
 }
 
-// Generate a random square of cells.
+/// Advance the engine by `steps` ticks, calling the step observer after each one. The run ends
+/// early if the observer returns non-zero; engine_get_generation tells how far it went.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_step(ptr: *mut EngineHandle, steps: u64) -> EngineStatus {
+    guard(|| {
+        for _ in 0..steps {
+            // The engine is not borrowed while the observer runs, so that it can call back in.
+            let (observer, user_data, generation, population) = {
+                let handle = handle_mut(ptr)?;
+                handle.step();
+                let (observer, user_data) = handle.step_observer();
+                let engine = handle.engine();
+                (
+                    observer,
+                    user_data,
+                    engine.get_generation(),
+                    engine.get_alive_cells_count(),
+                )
+            };
+            if let Some(observer) = observer
+                && observer(user_data, generation, population) != 0
+            {
+                break;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Get the number of ticks the engine was advanced by.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_get_generation(
+    ptr: *const EngineHandle,
+    generation: *mut u64,
+) -> EngineStatus {
+    guard(|| write_out(generation, "generation", engine_ref(ptr)?.get_generation()))
+}
+
+/* ----- Editing cells ----- */
+
+/// Activate a cell at (x, y).
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_activate_cell(ptr: *mut EngineHandle, x: u32, y: u32) -> EngineStatus {
+    guard(|| {
+        handle_mut(ptr)?
+            .edit(|e| e.activate_cell(x, y))
+            .map_err(Failure::invalid_argument)
+    })
+}
+
+/// Activate a set of cells. `cells` may only be null when `count` is 0.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_activate_cells(
+    ptr: *mut EngineHandle,
+    cells: *const Cell,
+    count: usize,
+) -> EngineStatus {
+    guard(|| {
+        let handle = handle_mut(ptr)?;
+        let cells = slice(cells, count, "cells")?;
+        handle
+            .edit(|e| e.activate_cells(cells))
+            .map_err(Failure::invalid_argument)
+    })
+}
+
+/// Deactivate the cell at (x, y).
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_deactivate_cell(ptr: *mut EngineHandle, x: u32, y: u32) -> EngineStatus {
+    guard(|| {
+        handle_mut(ptr)?
+            .edit(|e| e.deactivate_cell(x, y))
+            .map_err(Failure::invalid_argument)
+    })
+}
+
+/// Write whether the cell at (x, y) is alive into `is_alive`.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_is_cell_alive(
+    ptr: *const EngineHandle,
+    x: u32,
+    y: u32,
+    is_alive: *mut bool,
+) -> EngineStatus {
+    guard(|| write_out(is_alive, "is_alive", engine_ref(ptr)?.is_cell_alive(x, y)))
+}
+
+/// Deactivate every cell.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_clear(ptr: *mut EngineHandle) -> EngineStatus {
+    guard(|| {
+        let handle = handle_mut(ptr)?;
+        let engine = handle.engine();
+        let grid = Region::new(0, 0, engine.get_cols(), engine.get_rows());
+        handle.edit(|e| e.clear_region(&grid));
+        Ok(())
+    })
+}
+
+/// Deactivate the cells inside the rectangle of `width`×`height` cells whose top left cell is (x, y).
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_clear_rect(
+    ptr: *mut EngineHandle,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> EngineStatus {
+    guard(|| {
+        let region = Region::new(x, y, width, height);
+        handle_mut(ptr)?.edit(|e| e.clear_region(&region));
+        Ok(())
+    })
+}
+
+/// Generate a random square of cells.
 #[unsafe(no_mangle)]
 pub extern "C" fn engine_generate_random_square(
-    ptr: *mut LifeEngine,
+    ptr: *mut EngineHandle,
     top_left_x: u32,
     top_left_y: u32,
     size: u32,
-) {
-    if let Some(engine) = unsafe { ptr.as_mut() } {
-        engine.generate_random_square(Cell::new(top_left_x, top_left_y), size);
-    }
+) -> EngineStatus {
+    guard(|| {
+        handle_mut(ptr)?
+            .edit(|e| e.generate_random_square(Cell::new(top_left_x, top_left_y), size));
+        Ok(())
+    })
 }
 
-// Produce an iterator over the alive cells.
+/* ----- Querying cells ----- */
+
+/// Get the number of alive cells.
 #[unsafe(no_mangle)]
-fn engine_alive_cells_iterator_get<'a>(ptr: *const LifeEngine) -> *mut Iter<'a, Cell> {
-    if let Some(engine) = unsafe { ptr.as_ref() } {
-        Box::into_raw(Box::new(engine.get_alive_cells()))
-    } else {
-        std::ptr::null_mut()
-    }
+pub extern "C" fn engine_get_population(
+    ptr: *const EngineHandle,
+    population: *mut usize,
+) -> EngineStatus {
+    guard(|| {
+        write_out(
+            population,
+            "population",
+            engine_ref(ptr)?.get_alive_cells_count(),
+        )
+    })
+}
+
+/// Write the smallest rectangle containing every alive cell into `bounding_box`, and whether
+/// there are any alive cells into `has_cells`. The rectangle is left untouched when there are none.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_get_bounding_box(
+    ptr: *const EngineHandle,
+    bounding_box: *mut Region,
+    has_cells: *mut bool,
+) -> EngineStatus {
+    guard(|| {
+        let engine = engine_ref(ptr)?;
+        if bounding_box.is_null() {
+            return Err(Failure::null_pointer("bounding_box"));
+        }
+        let corners = engine.get_bounding_box();
+        write_out(has_cells, "has_cells", corners.is_some())?;
+        if let Some((top_left, bottom_right)) = corners {
+            write_out(
+                bounding_box,
+                "bounding_box",
+                Region::from_corners(&top_left, &bottom_right),
+            )?;
+        }
+        Ok(())
+    })
 }
 
-// Destroy an iterator previously created by engine_alive_cells_iterator_get.
+/// Copy the alive cells into `buffer`, which has room for `capacity` cells, in no particular order,
+/// and write the number of alive cells into `count`. When it is greater than `capacity` only the
+/// first `capacity` cells were copied, and the call should be repeated with a buffer of at least
+/// that size. Passing a null buffer and a capacity of 0 only queries the count.
 #[unsafe(no_mangle)]
-fn engine_alive_cells_iterator_free(ptr: *mut Iter<Cell>) {
+pub extern "C" fn engine_copy_alive_cells(
+    ptr: *const EngineHandle,
+    buffer: *mut Cell,
+    capacity: usize,
+    count: *mut usize,
+) -> EngineStatus {
+    guard(|| {
+        let engine = engine_ref(ptr)?;
+        let total = copy_cells(engine.get_alive_cells(), buffer, capacity)?;
+        write_out(count, "count", total)
+    })
+}
+
+/// Copy the alive cells inside the rectangle of `width`×`height` cells whose top left cell is
+/// (x, y) into `buffer`. Follows the same count/required-size protocol as engine_copy_alive_cells.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_copy_alive_cells_in_rect(
+    ptr: *const EngineHandle,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    buffer: *mut Cell,
+    capacity: usize,
+    count: *mut usize,
+) -> EngineStatus {
+    guard(|| {
+        let engine = engine_ref(ptr)?;
+        let region = Region::new(x, y, width, height);
+        let total = copy_cells(
+            engine.get_alive_cells().filter(|c| region.contains(c)),
+            buffer,
+            capacity,
+        )?;
+        write_out(count, "count", total)
+    })
+}
+
+/// Produce an iterator over a snapshot of the alive cells and write it into `iterator`.
+/// The iterator owns its copy of the cells: it goes on returning the cells alive when it was
+/// created, even once the engine is modified or freed.
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn engine_alive_cells_iterator_get(
+    ptr: *const EngineHandle,
+    iterator: *mut *mut CellsIterator,
+) -> EngineStatus {
+    guard(|| {
+        let handle = unsafe { ptr.as_ref() }.ok_or_else(|| Failure::null_pointer("engine"))?;
+        write_out(
+            iterator,
+            "iterator",
+            Box::into_raw(Box::new(CellsIterator::new(handle))),
+        )
+    })
+}
+
+/// Destroy an iterator previously created by engine_alive_cells_iterator_get. Freeing null does nothing.
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn engine_alive_cells_iterator_free(ptr: *mut CellsIterator) -> EngineStatus {
+    guard(|| {
+        if !ptr.is_null() {
+            drop(unsafe { Box::from_raw(ptr) });
+        }
+        Ok(())
+    })
+}
+
+/// Copy the next alive cell from the iterator into `cell` and write whether there was one into
+/// `has_cell`, which is false once all cells were seen.
+#[unsafe(no_mangle)]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn engine_alive_cells_iterator_next(
+    ptr: *mut CellsIterator,
+    cell: *mut Cell,
+    has_cell: *mut bool,
+) -> EngineStatus {
+    guard(|| {
+        let iterator = unsafe { ptr.as_mut() }.ok_or_else(|| Failure::null_pointer("iterator"))?;
+        if cell.is_null() {
+            return Err(Failure::null_pointer("cell"));
+        }
+        let next = iterator.next();
+        write_out(has_cell, "has_cell", next.is_some())?;
+        if let Some(next) = next {
+            write_out(cell, "cell", next)?;
+        }
+        Ok(())
+    })
+}
+
+/* ----- History ----- */
+
+/// Enable or disable the history. While it is enabled, edits of the cells can be undone and
+/// redone, and the engine can step back to earlier generations. Disabling it forgets everything.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_set_history(ptr: *mut EngineHandle, is_enabled: bool) -> EngineStatus {
+    guard(|| {
+        handle_mut(ptr)?.set_history(is_enabled);
+        Ok(())
+    })
+}
+
+/// Restore the cells from before the last edit, writing whether there was one to undo into `done`.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_undo(ptr: *mut EngineHandle, done: *mut bool) -> EngineStatus {
+    guard(|| {
+        let handle = handle_mut(ptr)?;
+        write_out(done, "done", handle.travel(|h, e| h.undo(e)))
+    })
+}
+
+/// Restore the cells undone last, writing whether there was something to redo into `done`.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_redo(ptr: *mut EngineHandle, done: *mut bool) -> EngineStatus {
+    guard(|| {
+        let handle = handle_mut(ptr)?;
+        write_out(done, "done", handle.travel(|h, e| h.redo(e)))
+    })
+}
+
+/// Go back to the previous generation, writing whether it was still known into `done`.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_step_back(ptr: *mut EngineHandle, done: *mut bool) -> EngineStatus {
+    guard(|| {
+        let handle = handle_mut(ptr)?;
+        write_out(done, "done", handle.travel(|h, e| h.step_back(e)))
+    })
+}
+
+/* ----- Patterns ----- */
+
+/// Activate the cells of an RLE pattern, given as `length` bytes of UTF-8 text, with its top
+/// left corner at (x, y). Cells falling outside of the grid are dropped. If the pattern
+/// specifies a rule, the engine switches to it. Like engine_set_rule, the rule change isn't
+/// recorded in the history: engine_undo removes the pattern's cells but keeps the new rule.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_load_rle(
+    ptr: *mut EngineHandle,
+    data: *const u8,
+    length: usize,
+    x: u32,
+    y: u32,
+) -> EngineStatus {
+    guard(|| {
+        let handle = handle_mut(ptr)?;
+        let document =
+            format::parse_rle(text(data, length, "data")?).map_err(Failure::invalid_argument)?;
+        if let Some(rule) = document.rule {
+            handle.engine_mut().set_rule(rule);
+        }
+        handle.edit(|e| e.stamp_pattern(&document.pattern, Cell::new(x, y)));
+        Ok(())
+    })
+}
+
+/// Copy the alive cells as an RLE pattern with the engine's rule into `buffer`, and write its
+/// size in bytes into `length`. The pattern starts at the top left corner of the bounding box
+/// given by engine_get_bounding_box. Follows the same protocol as engine_get_rule.
+#[unsafe(no_mangle)]
+pub extern "C" fn engine_save_rle(
+    ptr: *const EngineHandle,
+    buffer: *mut u8,
+    capacity: usize,
+    length: *mut usize,
+) -> EngineStatus {
+    guard(|| {
+        let engine = engine_ref(ptr)?;
+        let mut document = PatternDocument::new(engine.to_pattern().1);
+        document.rule = Some(engine.get_rule());
+        copy_bytes(
+            format::write_rle(&document).as_bytes(),
+            buffer,
+            capacity,
+            length,
+        )
+    })
+}
+
+/* ----- Argument handling ----- */
+
+fn engine_ref<'a>(ptr: *const EngineHandle) -> Result<&'a LifeEngine, Failure> {
+    let handle = unsafe { ptr.as_ref() }.ok_or_else(|| Failure::null_pointer("engine"))?;
+    Ok(handle.engine())
+}
+
+fn handle_mut<'a>(ptr: *mut EngineHandle) -> Result<&'a mut EngineHandle, Failure> {
+    unsafe { ptr.as_mut() }.ok_or_else(|| Failure::null_pointer("engine"))
+}
+
+// Write a result through an out pointer.
+fn write_out<T>(out: *mut T, name: &str, value: T) -> Result<(), Failure> {
+    if out.is_null() {
+        return Err(Failure::null_pointer(name));
+    }
+    unsafe { out.write(value) };
+    Ok(())
+}
+
+// Borrow `len` elements from the caller, checking the pointer and that the length fits in memory.
+fn slice<'a, T>(ptr: *const T, len: usize, name: &str) -> Result<&'a [T], Failure> {
+    check_length::<T>(len, name)?;
+    if len == 0 {
+        return Ok(&[]);
+    }
     if ptr.is_null() {
-        return;
+        return Err(Failure::null_pointer(name));
+    }
+    Ok(unsafe { std::slice::from_raw_parts(ptr, len) })
+}
+
+// Borrow `len` bytes of UTF-8 text from the caller.
+fn text<'a>(ptr: *const u8, len: usize, name: &str) -> Result<&'a str, Failure> {
+    std::str::from_utf8(slice(ptr, len, name)?)
+        .map_err(|e| Failure::invalid_argument(format!("{} is not valid UTF-8: {}", name, e)))
+}
+
+fn check_length<T>(len: usize, name: &str) -> Result<(), Failure> {
+    match len.checked_mul(size_of::<T>()) {
+        Some(size) if size <= isize::MAX as usize => Ok(()),
+        _ => Err(Failure::invalid_argument(format!(
+            "length of {} overflows: {}",
+            name, len
+        ))),
     }
-    unsafe {
-        drop(Box::from_raw(ptr));
+}
+
+// Copy as many cells as fit into `buffer` and return how many cells there are in total.
+fn copy_cells<'a>(
+    cells: impl Iterator<Item = &'a Cell>,
+    buffer: *mut Cell,
+    capacity: usize,
+) -> Result<usize, Failure> {
+    check_length::<Cell>(capacity, "buffer")?;
+    if buffer.is_null() && capacity > 0 {
+        return Err(Failure::null_pointer("buffer"));
+    }
+    let mut count = 0;
+    for cell in cells {
+        if count < capacity {
+            unsafe { buffer.add(count).write(cell.clone()) };
+        }
+        count += 1;
     }
+    Ok(count)
 }
 
-// Get the next alive cell from the iterator.
-#[unsafe(no_mangle)]
-fn engine_alive_cells_iterator_next(ptr: *mut Iter<Cell>) -> *const Cell {
-    if let Some(iterator) = unsafe { ptr.as_mut() } {
-        if let Some(cell) = iterator.next() {
-            &raw const (*cell)
-        } else {
-            std::ptr::null()
+// Copy `bytes` into `buffer` if they fit, and write how many there are into `length`.
+fn copy_bytes(
+    bytes: &[u8],
+    buffer: *mut u8,
+    capacity: usize,
+    length: *mut usize,
+) -> Result<(), Failure> {
+    check_length::<u8>(capacity, "buffer")?;
+    if buffer.is_null() && capacity > 0 {
+        return Err(Failure::null_pointer("buffer"));
+    }
+    if !bytes.is_empty() && bytes.len() <= capacity {
+        unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), buffer, bytes.len()) };
+    }
+    write_out(length, "length", bytes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn next_cells(iterator: *mut CellsIterator) -> Vec<Cell> {
+        let mut cells = Vec::new();
+        let (mut cell, mut has_cell) = (Cell::new(0, 0), false);
+        loop {
+            let status = engine_alive_cells_iterator_next(iterator, &mut cell, &mut has_cell);
+            assert_eq!(status, EngineStatus::Ok);
+            if !has_cell {
+                cells.sort();
+                return cells;
+            }
+            cells.push(cell.clone());
+        }
+    }
+
+    #[test]
+    fn iterators_outlive_changes_to_the_engine() {
+        let mut engine = std::ptr::null_mut();
+        assert_eq!(engine_new(8, 8, &mut engine), EngineStatus::Ok);
+        assert_eq!(engine_activate_cell(engine, 1, 2), EngineStatus::Ok);
+        assert_eq!(engine_activate_cell(engine, 3, 4), EngineStatus::Ok);
+        let mut iterator = std::ptr::null_mut();
+        assert_eq!(
+            engine_alive_cells_iterator_get(engine, &mut iterator),
+            EngineStatus::Ok
+        );
+
+        assert_eq!(engine_next(engine), EngineStatus::Ok);
+        assert_eq!(engine_free(engine), EngineStatus::Ok);
+        assert_eq!(next_cells(iterator), [Cell::new(1, 2), Cell::new(3, 4)]);
+        assert_eq!(next_cells(iterator), []);
+        assert_eq!(engine_alive_cells_iterator_free(iterator), EngineStatus::Ok);
+    }
+
+    // build.rs generates the header into OUT_DIR; this keeps the checked-in copy in sync.
+    // After changing the exported API, run the tests with UPDATE_FFI_HEADER=1 to update it.
+    #[test]
+    fn header_is_up_to_date() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/game_of_life_ffi.h"));
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/include/game_of_life_ffi.h");
+        assert!(
+            !generated.is_empty(),
+            "the header could not be generated, see the build warnings"
+        );
+        if std::env::var_os("UPDATE_FFI_HEADER").is_some() {
+            std::fs::write(path, generated).unwrap();
+            return;
         }
-    } else {
-        std::ptr::null()
+        let checked_in = std::fs::read_to_string(path).unwrap_or_default();
+        assert!(
+            checked_in == generated,
+            "include/game_of_life_ffi.h is out of date, run `UPDATE_FFI_HEADER=1 cargo test` to update it"
+        );
     }
 }
@@ -0,0 +1,79 @@
+use crate::simulation::Snapshot;
+use crate::ColorMode;
+use game_of_life_engine::Cell;
+use macroquad::color::Color;
+
+const NEWBORN_COLOR: Color = Color::new(1.0, 0.75, 0.1, 1.0);
+const ASH_COLOR: Color = Color::new(0.25, 0.3, 0.45, 1.0);
+/// Age, in generations, from which cells are drawn fully as ash.
+const ASH_AGE: f32 = 1000.0;
+/// Heat at which a cell is drawn about two thirds of the way to the hottest colour.
+const HEAT_SCALE: f32 = 3.0;
+
+/// Colour of a cell that has been alive for `age` generations. Newborn cells are bright and
+/// fade into ash on a logarithmic scale, so oscillators and fresh debris stand out from still lifes.
+pub fn age_color(age: u64) -> Color {
+    let t = ((1.0 + age as f32).ln() / (1.0 + ASH_AGE).ln()).min(1.0);
+    Color::new(
+        NEWBORN_COLOR.r + (ASH_COLOR.r - NEWBORN_COLOR.r) * t,
+        NEWBORN_COLOR.g + (ASH_COLOR.g - NEWBORN_COLOR.g) * t,
+        NEWBORN_COLOR.b + (ASH_COLOR.b - NEWBORN_COLOR.b) * t,
+        1.0,
+    )
+}
+
+/// Colour of a cell's heat, from a faint red for cells that changed a while ago
+/// to an opaque yellow for cells that keep changing.
+pub fn heat_color(heat: f32) -> Color {
+    let t = 1.0 - (-heat / HEAT_SCALE).exp();
+    Color::new(1.0, 0.1 + 0.8 * t, 0.0, 0.2 + 0.8 * t)
+}
+
+/// The alive cells of the snapshot with the colour they are drawn with in `mode`,
+/// where `alive` is the theme's colour for cells not coloured by age.
+pub fn cell_colors(
+    snapshot: &Snapshot,
+    mode: ColorMode,
+    alive: Color,
+) -> impl Iterator<Item = (&Cell, Color)> {
+    snapshot.cells.iter().enumerate().map(move |(i, cell)| {
+        let color = match mode {
+            ColorMode::Plain | ColorMode::Heat => alive,
+            ColorMode::Age => snapshot
+                .cell_ages
+                .get(i)
+                .map_or(alive, |age| age_color(*age)),
+        };
+        (cell, color)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Color, expected: Color) {
+        let channels = |c: Color| [c.r, c.g, c.b, c.a];
+        for (a, e) in channels(actual).into_iter().zip(channels(expected)) {
+            assert!((a - e).abs() < 1e-5, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn cells_fade_from_newborn_to_ash() {
+        assert_close(age_color(0), NEWBORN_COLOR);
+        assert_close(age_color(ASH_AGE as u64), ASH_COLOR);
+        assert_eq!(age_color(1_000_000), age_color(ASH_AGE as u64));
+        let reds: Vec<f32> = [0, 1, 10, 100, 999].map(|age| age_color(age).r).to_vec();
+        assert!(reds.windows(2).all(|pair| pair[0] > pair[1]), "{:?}", reds);
+    }
+
+    #[test]
+    fn heat_warms_from_faint_red_to_opaque_yellow() {
+        assert_eq!(heat_color(0.0), Color::new(1.0, 0.1, 0.0, 0.2));
+        assert_close(heat_color(100.0), Color::new(1.0, 0.9, 0.0, 1.0));
+        let hot = heat_color(HEAT_SCALE);
+        assert!(hot.a > 0.6 && hot.a < 0.8, "{:?}", hot);
+        assert!(heat_color(1.0).g < heat_color(2.0).g);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::simulation::Snapshot;
+use game_of_life_engine::{Cell, Region};
+
+/// How far, in cells, a followed object may move between two snapshots one generation
+/// apart and still be found, leaving room for it to change shape.
+const SEARCH_MARGIN: u32 = 4;
+
+enum Target {
+    /// The bounding box of all alive cells.
+    Everything,
+    /// The bounding box of a single object, e.g. a spaceship, found again in every
+    /// snapshot among the cells around where it was last seen.
+    Object(Region),
+}
+
+/// Follow mode: keeps track of what the camera should stay centred on as the world changes.
+pub struct Follow {
+    target: Option<Target>,
+    revision: Option<u64>,
+    /// Generation of the snapshot the target was last found in.
+    generation: u64,
+}
+
+impl Follow {
+    pub fn new() -> Self {
+        Self {
+            target: None,
+            revision: None,
+            generation: 0,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.target.is_some()
+    }
+
+    pub fn follow_everything(&mut self) {
+        self.target = Some(Target::Everything);
+        self.revision = None;
+    }
+
+    /// Follows the alive cells inside `region`. Returns false if there are none.
+    pub fn follow_object(&mut self, region: &Region, snapshot: &Snapshot) -> bool {
+        self.target = bounding_box(snapshot, region).map(Target::Object);
+        self.revision = None;
+        self.generation = snapshot.generation;
+        self.target.is_some()
+    }
+
+    pub fn stop(&mut self) {
+        self.target = None;
+    }
+
+    /// Returns the region the camera should be centred on, or `None` when not following.
+    /// Following stops when the followed object disappears.
+    pub fn update(&mut self, snapshot: &Snapshot) -> Option<Region> {
+        if self.revision != Some(snapshot.revision)
+            && let Some(Target::Object(object)) = &self.target
+        {
+            // Snapshots skip generations at high speeds. Spaceships move at most half a cell
+            // per generation, so the search grows with the generations elapsed since the last one.
+            let elapsed = snapshot.generation.abs_diff(self.generation);
+            let margin = u32::try_from(elapsed.div_ceil(2))
+                .unwrap_or(u32::MAX)
+                .saturating_add(SEARCH_MARGIN);
+            let search = Region::new(
+                object.x.saturating_sub(margin),
+                object.y.saturating_sub(margin),
+                object.width.saturating_add(margin.saturating_mul(2)),
+                object.height.saturating_add(margin.saturating_mul(2)),
+            );
+            self.target = bounding_box(snapshot, &search).map(Target::Object);
+        }
+        self.generation = snapshot.generation;
+        self.revision = Some(snapshot.revision);
+
+        match self.target.as_ref()? {
+            Target::Everything => snapshot
+                .bounding_box
+                .as_ref()
+                .map(|(top_left, bottom_right)| Region::from_corners(top_left, bottom_right)),
+            Target::Object(object) => Some(*object),
+        }
+    }
+}
+
+fn bounding_box(snapshot: &Snapshot, region: &Region) -> Option<Region> {
+    let mut cells = snapshot.cells.iter().filter(|c| region.contains(c));
+    let first = cells.next()?;
+    let (top_left, bottom_right) = cells.fold(
+        (first.clone(), first.clone()),
+        |(top_left, bottom_right), c| {
+            (
+                Cell::new(top_left.x.min(c.x), top_left.y.min(c.y)),
+                Cell::new(bottom_right.x.max(c.x), bottom_right.y.max(c.y)),
+            )
+        },
+    );
+    Some(Region::from_corners(&top_left, &bottom_right))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lod::DensityBins;
+    use game_of_life_engine::Rule;
+
+    fn snapshot(revision: u64, generation: u64, cells: &[(u32, u32)]) -> Snapshot {
+        let cells: Vec<Cell> = cells.iter().map(|(x, y)| Cell::new(*x, *y)).collect();
+        let bounding_box = cells.first().map(|first| {
+            cells.iter().fold(
+                (first.clone(), first.clone()),
+                |(top_left, bottom_right), c| {
+                    (
+                        Cell::new(top_left.x.min(c.x), top_left.y.min(c.y)),
+                        Cell::new(bottom_right.x.max(c.x), bottom_right.y.max(c.y)),
+                    )
+                },
+            )
+        });
+        Snapshot {
+            revision,
+            generation,
+            rule: Rule::default(),
+            cells,
+            cell_ages: Vec::new(),
+            heat: Vec::new(),
+            bounding_box,
+            overview: DensityBins::new(Cell::new(0, 0), &Cell::new(0, 0), 1),
+            last_step: None,
+            step_populations: Vec::new(),
+            is_running: true,
+            speed_label: String::new(),
+            is_budgeted: true,
+            dropped_steps: 0,
+        }
+    }
+
+    /// A 2×2 block at `(x, y)` and a lone cell far away from it.
+    fn block_at(revision: u64, generation: u64, x: u32, y: u32) -> Snapshot {
+        let cells = [(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1), (200, 200)];
+        snapshot(revision, generation, &cells)
+    }
+
+    #[test]
+    fn following_everything_centres_on_all_alive_cells() {
+        let mut follow = Follow::new();
+        assert_eq!(follow.update(&block_at(0, 0, 10, 10)), None);
+        follow.follow_everything();
+        assert_eq!(
+            follow.update(&block_at(1, 1, 10, 10)),
+            Some(Region::new(10, 10, 191, 191))
+        );
+        assert_eq!(follow.update(&snapshot(2, 2, &[])), None);
+        assert!(follow.is_active());
+    }
+
+    #[test]
+    fn followed_object_is_found_where_it_moved() {
+        let mut follow = Follow::new();
+        assert!(follow.follow_object(&Region::new(8, 8, 6, 6), &block_at(0, 0, 10, 10)));
+        assert_eq!(
+            follow.update(&block_at(1, 1, 13, 8)),
+            Some(Region::new(13, 8, 2, 2))
+        );
+        assert_eq!(
+            follow.update(&block_at(2, 2, 16, 6)),
+            Some(Region::new(16, 6, 2, 2))
+        );
+    }
+
+    #[test]
+    fn search_grows_with_the_generations_skipped() {
+        let mut follow = Follow::new();
+        follow.follow_object(&Region::new(10, 10, 2, 2), &block_at(0, 0, 10, 10));
+        // 30 generations allow 15 cells plus the margin.
+        assert_eq!(
+            follow.update(&block_at(1, 30, 29, 10)),
+            Some(Region::new(29, 10, 2, 2))
+        );
+    }
+
+    #[test]
+    fn following_stops_when_the_object_is_lost() {
+        let mut follow = Follow::new();
+        follow.follow_object(&Region::new(10, 10, 2, 2), &block_at(0, 0, 10, 10));
+        assert_eq!(follow.update(&block_at(1, 1, 30, 10)), None);
+        assert!(!follow.is_active());
+        assert!(!follow.follow_object(&Region::new(50, 50, 10, 10), &block_at(2, 2, 10, 10)));
+    }
+}
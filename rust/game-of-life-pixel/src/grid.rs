@@ -0,0 +1,69 @@
+use game_of_life_engine::Cell;
+use macroquad::color::Color;
+use macroquad::shapes::draw_line;
+
+/// Spacings, in cells, of the grid line sets: every cell, then major lines.
+const SPACINGS: [u32; 3] = [1, 10, 100];
+/// Line sets closer together than this on screen are not drawn.
+const MIN_LINE_PIXELS: f32 = 8.0;
+
+/// Draws the grid lines between `top_left` and `bottom_right` (inclusive) in world coordinates.
+/// Only the line sets far enough apart at the current zoom are drawn, and each set of major
+/// lines is drawn on top of the finer ones so that it stands out.
+pub fn draw_grid(
+    top_left: &Cell,
+    bottom_right: &Cell,
+    cell_size: f32,
+    pixels_per_cell: f32,
+    color: Color,
+) {
+    let pixel = cell_size / pixels_per_cell;
+    let left = top_left.x as f32 * cell_size;
+    let top = top_left.y as f32 * cell_size;
+    let right = (bottom_right.x + 1) as f32 * cell_size;
+    let bottom = (bottom_right.y + 1) as f32 * cell_size;
+    for spacing in SPACINGS {
+        if spacing as f32 * pixels_per_cell < MIN_LINE_PIXELS {
+            continue;
+        }
+        for x in lines(top_left.x, bottom_right.x + 1, spacing) {
+            let x = x as f32 * cell_size;
+            draw_line(x, top, x, bottom, pixel, color);
+        }
+        for y in lines(top_left.y, bottom_right.y + 1, spacing) {
+            let y = y as f32 * cell_size;
+            draw_line(left, y, right, y, pixel, color);
+        }
+    }
+}
+
+/// Positions of the lines every `spacing` cells between `from` and `to` (inclusive).
+fn lines(from: u32, to: u32, spacing: u32) -> impl Iterator<Item = u32> {
+    (from.div_ceil(spacing) * spacing..=to).step_by(spacing as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_lines(from: u32, to: u32, spacing: u32) -> Vec<u32> {
+        lines(from, to, spacing).collect()
+    }
+
+    #[test]
+    fn lines_are_on_multiples_of_the_spacing() {
+        assert_eq!(all_lines(0, 25, 10), [0, 10, 20]);
+        assert_eq!(all_lines(3, 30, 10), [10, 20, 30]);
+        assert_eq!(all_lines(95, 205, 100), [100, 200]);
+    }
+
+    #[test]
+    fn every_cell_edge_is_a_line_at_spacing_one() {
+        assert_eq!(all_lines(4, 7, 1), [4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn ranges_between_two_lines_have_none() {
+        assert_eq!(all_lines(11, 19, 10), []);
+    }
+}
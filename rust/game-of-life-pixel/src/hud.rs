@@ -0,0 +1,166 @@
+use crate::simulation::Snapshot;
+use macroquad::color::{Color, LIME, WHITE};
+use macroquad::shapes::{draw_line, draw_rectangle};
+use macroquad::text::draw_text;
+use macroquad::time::{get_fps, get_time};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+const PANEL_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.75);
+const PANEL_WIDTH: f32 = 320.0;
+const LINE_HEIGHT: f32 = 22.0;
+const FONT_SIZE: f32 = 20.0;
+const CHART_HEIGHT: f32 = 100.0;
+const PADDING: f32 = 10.0;
+/// How often the generations per second figure is recomputed, in seconds.
+const RATE_SAMPLE_PERIOD: f64 = 0.5;
+
+/// Toggleable statistics overlay with a scrolling chart of the population.
+pub struct Hud {
+    is_visible: bool,
+    history_length: usize,
+    population_history: VecDeque<usize>,
+    last_births: usize,
+    last_deaths: usize,
+    last_step_time: Duration,
+    steps_since_sample: u32,
+    sample_start: f64,
+    generations_per_second: f64,
+}
+
+impl Hud {
+    pub fn new(history_length: usize) -> Self {
+        Self {
+            is_visible: false,
+            history_length,
+            population_history: VecDeque::with_capacity(history_length),
+            last_births: 0,
+            last_deaths: 0,
+            last_step_time: Duration::ZERO,
+            steps_since_sample: 0,
+            sample_start: get_time(),
+            generations_per_second: 0.0,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.is_visible = !self.is_visible;
+    }
+
+    /// Records the steps that led to a newly received snapshot.
+    pub fn record(&mut self, snapshot: &Snapshot) {
+        if let Some(step) = &snapshot.last_step {
+            self.last_births = step.births.len();
+            self.last_deaths = step.deaths.len();
+            self.last_step_time = step.elapsed;
+        }
+        self.steps_since_sample += snapshot.step_populations.len() as u32;
+        for population in &snapshot.step_populations {
+            if self.population_history.len() == self.history_length {
+                self.population_history.pop_front();
+            }
+            self.population_history.push_back(*population);
+        }
+    }
+
+    /// Draws the overlay in screen coordinates, with its top left corner at (`x`, `y`).
+    pub fn draw(&mut self, snapshot: &Snapshot, x: f32, y: f32) {
+        let now = get_time();
+        if now - self.sample_start >= RATE_SAMPLE_PERIOD {
+            self.generations_per_second =
+                self.steps_since_sample as f64 / (now - self.sample_start);
+            self.steps_since_sample = 0;
+            self.sample_start = now;
+        }
+        if !self.is_visible {
+            return;
+        }
+
+        let lines = [
+            format!("Generation: {}", snapshot.generation),
+            format!("Population: {}", snapshot.cells.len()),
+            format!(
+                "Births / deaths: {} / {}",
+                self.last_births, self.last_deaths
+            ),
+            format!(
+                "Step time: {:.2} ms",
+                self.last_step_time.as_secs_f64() * 1000.0
+            ),
+            format!("FPS: {}", get_fps()),
+            format!("Generations/s: {:.1}", self.generations_per_second),
+        ];
+        let text_height = lines.len() as f32 * LINE_HEIGHT;
+        draw_rectangle(
+            x,
+            y,
+            PANEL_WIDTH,
+            text_height + CHART_HEIGHT + 3.0 * PADDING,
+            PANEL_COLOR,
+        );
+        for (i, line) in lines.iter().enumerate() {
+            draw_text(
+                line,
+                x + PADDING,
+                y + PADDING + (i + 1) as f32 * LINE_HEIGHT - 6.0,
+                FONT_SIZE,
+                WHITE,
+            );
+        }
+
+        self.draw_chart(
+            x + PADDING,
+            y + 2.0 * PADDING + text_height,
+            PANEL_WIDTH - 2.0 * PADDING,
+        );
+    }
+
+    fn draw_chart(&self, x: f32, y: f32, width: f32) {
+        draw_line(x, y + CHART_HEIGHT, x + width, y + CHART_HEIGHT, 1.0, WHITE);
+        if self.population_history.len() < 2 {
+            return;
+        }
+        let max_population = self
+            .population_history
+            .iter()
+            .max()
+            .copied()
+            .unwrap_or(0)
+            .max(1);
+        let min_population = self.population_history.iter().min().copied().unwrap_or(0);
+        let range = (max_population - min_population).max(1) as f32;
+        let step = width / (self.history_length.max(2) - 1) as f32;
+        let to_point = |i: usize, population: usize| {
+            (
+                x + i as f32 * step,
+                y + CHART_HEIGHT - (population - min_population) as f32 / range * CHART_HEIGHT,
+            )
+        };
+
+        let mut points = self
+            .population_history
+            .iter()
+            .enumerate()
+            .map(|(i, population)| to_point(i, *population));
+        if let Some(mut previous) = points.next() {
+            for point in points {
+                draw_line(previous.0, previous.1, point.0, point.1, 1.5, LIME);
+                previous = point;
+            }
+        }
+        draw_text(
+            &max_population.to_string(),
+            x,
+            y + FONT_SIZE * 0.6,
+            FONT_SIZE * 0.8,
+            WHITE,
+        );
+        draw_text(
+            &min_population.to_string(),
+            x,
+            y + CHART_HEIGHT - 4.0,
+            FONT_SIZE * 0.8,
+            WHITE,
+        );
+    }
+}
@@ -1,24 +1,136 @@
-use game_of_life_engine::{Cell, LifeEngine};
-use macroquad::camera::{set_camera, Camera2D};
-use macroquad::color::{BLACK, LIME, WHITE};
+mod colors;
+mod follow;
+mod grid;
+mod hud;
+mod lod;
+mod minimap;
+mod palette;
+mod pattern_file;
+mod screenshot;
+mod selection;
+mod simulation;
+mod speed;
+mod texture;
+mod theme;
+
+use crate::follow::Follow;
+use crate::hud::Hud;
+use crate::lod::DensityBins;
+use crate::minimap::Minimap;
+use crate::palette::Palette;
+use crate::selection::{is_ctrl_down, is_shift_down, Selection};
+use crate::simulation::{Command, Simulation, Snapshot};
+use crate::speed::Scheduler;
+use crate::texture::TextureRenderer;
+pub use crate::theme::Theme;
+use game_of_life_engine::format::{PatternDocument, PatternFormat};
+use game_of_life_engine::{Cell, LifeEngine, Pattern, Region, Rule};
+use macroquad::camera::{set_camera, set_default_camera, Camera2D};
+use macroquad::color::LIME;
 use macroquad::input::{
     is_key_pressed, is_mouse_button_down, is_mouse_button_pressed, mouse_delta_position, mouse_position,
     mouse_wheel, KeyCode, MouseButton,
 };
 use macroquad::math::{clamp, vec2, Rect, Vec2};
-use macroquad::prelude::{get_frame_time, get_screen_data};
+use macroquad::miniquad::window::{clipboard_get, clipboard_set};
+use macroquad::prelude::get_screen_data;
 use macroquad::shapes::{draw_rectangle, draw_rectangle_lines};
 use macroquad::ui::{root_ui, Skin};
-use macroquad::window::{clear_background, next_frame, Conf};
+use macroquad::window::{clear_background, next_frame, screen_height, screen_width, Conf};
 use std::cmp::min;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Largest visible region, in cells per side, drawn through a single texture.
+const MAX_TEXTURE_CELLS: u32 = 4096;
+/// Empty space, in cells, left around the pattern when fitting the camera to it.
+const FIT_MARGIN: f32 = 10.0;
+
+/// How alive cells are drawn when zoomed in far enough to see individual cells.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum RenderMode {
+    /// One rectangle per visible cell.
+    Shapes,
+    /// The visible region rasterised on the CPU and uploaded as a single texture.
+    Texture,
+}
+
+impl FromStr for RenderMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "shapes" => Ok(RenderMode::Shapes),
+            "texture" => Ok(RenderMode::Texture),
+            _ => Err(format!("unknown render mode '{}', expected shapes or texture", s)),
+        }
+    }
+}
+
+/// What the colour of an alive cell tells.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ColorMode {
+    /// All alive cells look the same.
+    Plain,
+    /// Cells fade from bright to ash the longer they live.
+    Age,
+    /// Cells are drawn over a heat map of recent births and deaths.
+    Heat,
+}
+
+impl ColorMode {
+    fn next(self) -> ColorMode {
+        match self {
+            ColorMode::Plain => ColorMode::Age,
+            ColorMode::Age => ColorMode::Heat,
+            ColorMode::Heat => ColorMode::Plain,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ColorMode::Plain => "plain",
+            ColorMode::Age => "age",
+            ColorMode::Heat => "heat",
+        }
+    }
+}
+
+impl FromStr for ColorMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "plain" => Ok(ColorMode::Plain),
+            "age" => Ok(ColorMode::Age),
+            "heat" => Ok(ColorMode::Heat),
+            _ => Err(format!("unknown colour mode '{}', expected plain, age or heat", s)),
+        }
+    }
+}
 
 pub struct GameConfig {
     cols: u32,
     rows: u32,
     cell_size: f32,
     game_iterations_per_second: f32,
+    frame_budget: Duration,
+    multi_step_count: u32,
     min_zoom: f32,
     max_zoom: f32,
+    lod_cell_pixels: f32,
+    lod_bin_pixels: f32,
+    render_mode: RenderMode,
+    color_mode: ColorMode,
+    heat_decay: f32,
+    theme: Theme,
+    show_grid: bool,
+    population_history_length: usize,
+    minimap_size: f32,
+    random_fill_density: f64,
+    rule: Option<Rule>,
+    save_directory: PathBuf,
 }
 
 impl GameConfig {
@@ -28,21 +140,72 @@ impl GameConfig {
             rows,
             cell_size: 5.0,
             game_iterations_per_second: 10.0,
+            frame_budget: Duration::from_millis(12),
+            multi_step_count: 100,
             min_zoom: 0.00001,
             max_zoom: 1.0,
+            lod_cell_pixels: 1.0,
+            lod_bin_pixels: 2.0,
+            render_mode: RenderMode::Shapes,
+            color_mode: ColorMode::Plain,
+            heat_decay: 0.95,
+            theme: Theme::LIGHT,
+            show_grid: true,
+            population_history_length: 500,
+            minimap_size: 200.0,
+            random_fill_density: 0.3,
+            rule: None,
+            save_directory: PathBuf::from("."),
         }
     }
+
+    /// Sets the rule to run. When unset, the rule stored in a loaded pattern file
+    /// is used, or B3/S23.
+    pub fn with_rule(mut self, rule: Option<Rule>) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    pub fn with_render_mode(mut self, render_mode: RenderMode) -> Self {
+        self.render_mode = render_mode;
+        self
+    }
+
+    pub fn with_color_mode(mut self, color_mode: ColorMode) -> Self {
+        self.color_mode = color_mode;
+        self
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Sets the directory where generations are saved.
+    pub fn with_save_directory(mut self, save_directory: PathBuf) -> Self {
+        self.save_directory = save_directory;
+        self
+    }
 }
 pub struct Game {
-    engine: LifeEngine,
+    simulation: Simulation,
+    snapshot: Snapshot,
     camera: Camera2D,
-    is_running: bool,
-    step: f32,
-    accumulator: f32,
+    follow: Follow,
     random_cells_square_size: u32,
     min_zoom_vec: Vec2,
     max_zoom_vec: Vec2,
     skin: Skin,
+    palette: Palette,
+    selection: Selection,
+    /// Last copied cells, used when the system clipboard holds no readable pattern.
+    clipboard: Option<Pattern>,
+    texture_renderer: TextureRenderer,
+    hud: Hud,
+    minimap: Minimap,
+    color_mode: ColorMode,
+    last_file: Option<PathBuf>,
+    status_message: Option<String>,
     config: GameConfig,
 }
 
@@ -69,18 +232,48 @@ impl Game {
             ..root_ui().default_skin()
         };
 
-        Self {
-            engine: LifeEngine::new(config.cols, config.rows),
+        let mut engine = LifeEngine::new(config.cols, config.rows);
+        engine.set_rule(config.rule.unwrap_or_default());
+        let scheduler = Scheduler::new(config.game_iterations_per_second, config.frame_budget);
+        let simulation = Simulation::spawn(engine, scheduler, config.minimap_size as u32);
+        let snapshot = simulation
+            .take_snapshot()
+            .expect("the simulation publishes a snapshot when spawned");
+
+        let mut game = Self {
+            simulation,
+            snapshot,
             camera,
-            is_running: false,
-            step: 1.0 / config.game_iterations_per_second,
-            accumulator: 0.0,
+            follow: Follow::new(),
             random_cells_square_size,
             min_zoom_vec: Vec2::new(config.min_zoom, config.min_zoom),
             max_zoom_vec: Vec2::new(config.max_zoom, config.max_zoom),
             skin,
+            palette: Palette::new(),
+            selection: Selection::new(),
+            clipboard: None,
+            texture_renderer: TextureRenderer::new(config.theme),
+            hud: Hud::new(config.population_history_length),
+            minimap: Minimap::new(config.minimap_size),
+            color_mode: ColorMode::Plain,
+            last_file: None,
+            status_message: None,
             config,
+        };
+        game.set_color_mode(game.config.color_mode);
+        game
+    }
+
+    /// Replaces the current world with the pattern read from `path` and centers the camera on it.
+    pub fn load_pattern_file(&mut self, path: &Path) -> Result<(), String> {
+        let engine =
+            pattern_file::load(path, self.config.cols, self.config.rows, self.config.rule)?;
+        self.last_file = Some(path.to_path_buf());
+        if let Some((top_left, bottom_right)) = engine.get_bounding_box() {
+            self.center_on(&Region::from_corners(&top_left, &bottom_right));
         }
+        self.simulation.send(Command::Replace(Box::new(engine)));
+        Ok(())
     }
 
     pub async fn start(&mut self) {
@@ -90,31 +283,146 @@ impl Game {
     }
 
     async fn on_update(&mut self) {
+        self.receive_snapshot();
+        self.handle_camera_modes();
         self.handle_run_toggle();
+        self.handle_speed();
+        self.handle_hud_toggle();
+        self.handle_minimap();
+        self.handle_color_mode();
         self.handle_move();
         self.handle_zoom();
-        self.handle_generate_square();
+        self.palette.handle_input();
+        self.selection.handle_input(self.cursor_cell());
+        self.handle_selection_edit();
+        self.handle_place();
         self.handle_next_generation();
-        self.handle_generate_square();
+        self.handle_history();
+        self.handle_save_and_reload();
 
-        self.run_engine();
         self.draw_cells();
 
         next_frame().await;
     }
 
+    fn receive_snapshot(&mut self) {
+        if let Some(snapshot) = self.simulation.take_snapshot() {
+            self.hud.record(&snapshot);
+            self.snapshot = snapshot;
+        }
+    }
+
+    fn handle_camera_modes(&mut self) {
+        if is_key_pressed(KeyCode::Home) {
+            match self.snapshot.bounding_box.clone() {
+                Some((top_left, bottom_right)) => {
+                    self.fit_to(&Region::from_corners(&top_left, &bottom_right))
+                }
+                None => {
+                    self.status_message = Some(String::from("Nothing to fit, the world is empty"))
+                }
+            }
+        }
+        if is_key_pressed(KeyCode::L) {
+            if self.follow.is_active() {
+                self.follow.stop();
+                self.status_message = Some(String::from("Stopped following"));
+            } else if let Some(region) = self.selection.region().cloned() {
+                self.status_message = Some(String::from(
+                    if self.follow.follow_object(&region, &self.snapshot) {
+                        "Following the selected object"
+                    } else {
+                        "No alive cells in the selection to follow"
+                    },
+                ));
+            } else {
+                self.follow.follow_everything();
+                self.status_message = Some(String::from("Following all alive cells"));
+            }
+        }
+        if let Some(region) = self.follow.update(&self.snapshot) {
+            self.center_on(&region);
+        } else if self.follow.is_active() {
+            self.follow.stop();
+            self.status_message = Some(String::from("Stopped following, nothing left to follow"));
+        }
+    }
+
+    /// Zooms and moves the camera so that `region` fills the screen.
+    fn fit_to(&mut self, region: &Region) {
+        let cell_size = self.config.cell_size;
+        let width = (region.width as f32 + 2.0 * FIT_MARGIN) * cell_size;
+        let height = (region.height as f32 + 2.0 * FIT_MARGIN) * cell_size;
+        let zoom = (2.0 / width).min(2.0 / height);
+        self.camera.zoom = Vec2::clamp(Vec2::splat(zoom), self.min_zoom_vec, self.max_zoom_vec);
+        self.center_on(region);
+    }
+
+    fn center_on(&mut self, region: &Region) {
+        let center = Vec2::new(
+            region.x as f32 + region.width as f32 / 2.0,
+            region.y as f32 + region.height as f32 / 2.0,
+        );
+        self.camera.target = center * self.config.cell_size;
+    }
+
     fn handle_run_toggle(&mut self) {
         if is_key_pressed(KeyCode::Space) {
-            self.is_running = !self.is_running;
-            if self.is_running {
-                self.accumulator = 0.0;
-            }
+            self.simulation.send(Command::ToggleRunning);
+        }
+    }
+
+    fn handle_speed(&mut self) {
+        if is_key_pressed(KeyCode::Equal) || is_key_pressed(KeyCode::KpAdd) {
+            self.simulation.send(Command::Faster);
+        }
+        if is_key_pressed(KeyCode::Minus) || is_key_pressed(KeyCode::KpSubtract) {
+            self.simulation.send(Command::Slower);
+        }
+        if is_key_pressed(KeyCode::B) {
+            self.simulation.send(Command::ToggleBudget);
+        }
+    }
+
+    fn handle_hud_toggle(&mut self) {
+        if is_key_pressed(KeyCode::H) {
+            self.hud.toggle();
+        }
+    }
+
+    fn handle_minimap(&mut self) {
+        if is_key_pressed(KeyCode::M) {
+            self.minimap.toggle();
+        }
+        if let Some(position) = self.minimap.handle_input(self.config.cols, self.config.rows) {
+            self.camera.target = position * self.config.cell_size;
+            self.follow.stop();
+        }
+    }
+
+    fn handle_color_mode(&mut self) {
+        if is_key_pressed(KeyCode::C) && !is_ctrl_down() {
+            self.set_color_mode(self.color_mode.next());
         }
+        if is_key_pressed(KeyCode::G) && !is_ctrl_down() {
+            self.config.show_grid = !self.config.show_grid;
+        }
+    }
+
+    /// Switches the colour mode, asking the simulation to track only what the mode needs.
+    fn set_color_mode(&mut self, color_mode: ColorMode) {
+        self.color_mode = color_mode;
+        self.simulation
+            .send(Command::SetAgeTracking(color_mode == ColorMode::Age));
+        self.simulation.send(Command::SetHeatMap(
+            (color_mode == ColorMode::Heat).then_some(self.config.heat_decay),
+        ));
     }
 
     fn handle_move(&mut self) {
         if is_mouse_button_down(MouseButton::Right) {
             self.camera.target += mouse_delta_position() / self.camera.zoom;
+            self.follow.stop();
         }
     }
 
@@ -137,45 +445,176 @@ impl Game {
         }
     }
 
-    fn handle_generate_square(&mut self) {
-        if is_mouse_button_pressed(MouseButton::Left) {
-            let wp = self.camera.screen_to_world(mouse_position().into());
-            let center_cell = self.to_cell(wp);
-            let top_left_cell = Cell::new(
-                clamp(
-                    u32::saturating_sub(center_cell.x, self.random_cells_square_size / 2),
-                    0,
-                    self.config.cols - 1,
-                ),
-                clamp(
-                    u32::saturating_sub(center_cell.y, self.random_cells_square_size / 2),
-                    0,
-                    self.config.rows - 1,
-                ),
-            );
-            self.engine
-                .generate_random_square(top_left_cell, self.random_cells_square_size);
+    fn handle_place(&mut self) {
+        if !is_mouse_button_pressed(MouseButton::Left)
+            || is_shift_down()
+            || root_ui().is_mouse_over(mouse_position().into())
+            || self.minimap.contains(mouse_position().into(), self.config.cols, self.config.rows)
+        {
+            return;
+        }
+        let center_cell = self.cursor_cell();
+        match (self.palette.selected_pattern(), self.palette.placement(&center_cell)) {
+            (Some(pattern), Some(top_left_cell)) => self
+                .simulation
+                .send(Command::Stamp(pattern.clone(), top_left_cell)),
+            _ => self.generate_square(center_cell),
         }
     }
 
-    fn handle_next_generation(&mut self) {
-        if is_key_pressed(KeyCode::Enter) {
-            self.engine.next()
+    fn handle_selection_edit(&mut self) {
+        if is_ctrl_down() && is_key_pressed(KeyCode::V) {
+            self.paste();
         }
+        let Some(region) = self.selection.region().cloned() else {
+            return;
+        };
+        if is_ctrl_down() {
+            if is_key_pressed(KeyCode::C) {
+                self.copy(&region);
+            }
+            if is_key_pressed(KeyCode::X) {
+                self.copy(&region);
+                self.simulation.send(Command::ClearRegion(region));
+            }
+            if is_key_pressed(KeyCode::G) {
+                self.simulation.send(Command::FillRegion(
+                    region,
+                    self.config.random_fill_density,
+                ));
+            }
+            if is_key_pressed(KeyCode::I) {
+                self.simulation.send(Command::InvertRegion(region));
+            }
+        } else if is_key_pressed(KeyCode::Delete) || is_key_pressed(KeyCode::Backspace) {
+            self.simulation.send(Command::ClearRegion(region));
+        }
+    }
+
+    /// Copies the selected cells, also putting them on the system clipboard as RLE.
+    fn copy(&mut self, region: &Region) {
+        let pattern = Pattern::from_region(region, self.snapshot.cells.iter());
+        let mut document = PatternDocument::new(pattern.clone());
+        document.rule = Some(self.snapshot.rule);
+        clipboard_set(&PatternFormat::Rle.write(&document));
+        self.status_message = Some(format!(
+            "Copied {} cells",
+            pattern.get_alive_cells_count()
+        ));
+        self.clipboard = Some(pattern);
     }
 
-    fn run_engine(&mut self) {
-        if self.is_running {
-            self.accumulator += get_frame_time();
-            while self.accumulator >= self.step {
-                self.engine.next();
-                self.accumulator -= self.step;
+    /// Picks up the clipboard contents as the pattern to place, so that it can be
+    /// rotated and flipped like a catalogue pattern before being stamped.
+    fn paste(&mut self) {
+        let pasted = clipboard_get()
+            .filter(|text| !text.trim().is_empty())
+            .and_then(|text| PatternFormat::detect(&text).parse(&text).ok())
+            .map(|document| document.pattern)
+            .filter(|pattern| pattern.get_alive_cells_count() > 0)
+            .or_else(|| self.clipboard.clone());
+        match pasted {
+            Some(pattern) => {
+                self.palette.select("Clipboard", pattern);
+                self.status_message = Some(String::from("Click to paste"));
             }
+            None => self.status_message = Some(String::from("Nothing to paste")),
+        }
+    }
+
+    fn generate_square(&mut self, center_cell: Cell) {
+        let top_left_cell = Cell::new(
+            clamp(
+                u32::saturating_sub(center_cell.x, self.random_cells_square_size / 2),
+                0,
+                self.config.cols - 1,
+            ),
+            clamp(
+                u32::saturating_sub(center_cell.y, self.random_cells_square_size / 2),
+                0,
+                self.config.rows - 1,
+            ),
+        );
+        self.simulation.send(Command::RandomSquare(
+            top_left_cell,
+            self.random_cells_square_size,
+        ));
+    }
+
+    fn handle_next_generation(&mut self) {
+        // Shift+Enter steps back instead, see handle_history.
+        if is_key_pressed(KeyCode::Enter) && !is_shift_down() {
+            self.simulation.send(Command::Step(1));
+        }
+        if is_key_pressed(KeyCode::N) {
+            self.simulation
+                .send(Command::Step(self.config.multi_step_count));
+        }
+    }
+
+    fn handle_history(&mut self) {
+        if is_ctrl_down() && is_key_pressed(KeyCode::Z) {
+            self.simulation.send(if is_shift_down() {
+                Command::Redo
+            } else {
+                Command::Undo
+            });
+        }
+        if is_ctrl_down() && is_key_pressed(KeyCode::Y) {
+            self.simulation.send(Command::Redo);
+        }
+        if is_shift_down() && is_key_pressed(KeyCode::Enter) {
+            self.simulation.send(Command::StepBack);
+        }
+    }
+
+    fn handle_save_and_reload(&mut self) {
+        if is_key_pressed(KeyCode::F5) {
+            self.status_message = Some(
+                match pattern_file::save(&self.snapshot, &self.config.save_directory) {
+                    Ok(path) => {
+                        let message = format!("Saved {}", path.display());
+                        self.last_file = Some(path);
+                        message
+                    }
+                    Err(e) => e,
+                },
+            );
+        }
+        if is_key_pressed(KeyCode::F12) {
+            let top_left = self.to_cell(self.camera.screen_to_world(Vec2::ZERO));
+            let bottom_right =
+                self.to_cell(self.camera.screen_to_world(vec2(screen_width(), screen_height())));
+            let viewport = Region::from_corners(&top_left, &bottom_right);
+            let scale = (screen_width() / viewport.width as f32).round() as u32;
+            self.status_message = Some(
+                match screenshot::save_view(
+                    &self.snapshot,
+                    viewport,
+                    scale,
+                    self.color_mode,
+                    &self.config.theme,
+                    &self.config.save_directory,
+                ) {
+                    Ok(path) => format!("Saved {}", path.display()),
+                    Err(e) => e,
+                },
+            );
+        }
+        if is_key_pressed(KeyCode::F9) {
+            self.status_message = Some(match self.last_file.clone() {
+                Some(path) => match self.load_pattern_file(&path) {
+                    Ok(()) => format!("Loaded {}", path.display()),
+                    Err(e) => e,
+                },
+                None => String::from("Nothing to reload, press F5 to save first"),
+            });
         }
     }
 
     fn draw_cells(&mut self) {
-        clear_background(WHITE);
+        let theme = self.config.theme;
+        clear_background(theme.background);
         set_camera(&self.camera);
         let visible_top_left = self.camera.screen_to_world(Vec2::new(0.0, 0.0));
         let visible_top_right = self.camera.screen_to_world(Vec2::new(
@@ -186,40 +625,139 @@ impl Game {
         let visible_top_left_cell = self.to_cell(visible_top_left);
         let visible_top_right_cell = self.to_cell(visible_top_right);
 
+        // When a cell covers less than a screen pixel, cells are aggregated into bins
+        // a couple of pixels wide instead of being drawn one by one.
         let cell_size = self.config.cell_size;
-        self.engine
-            .get_alive_cells()
-            .filter(|c| {
+        let pixels_per_cell =
+            screen_width() / ((visible_top_right.x - visible_top_left.x) / cell_size);
+        if pixels_per_cell < self.config.lod_cell_pixels {
+            let bin_size = (self.config.lod_bin_pixels / pixels_per_cell).ceil() as u32;
+            let mut bins =
+                DensityBins::new(visible_top_left_cell.clone(), &visible_top_right_cell, bin_size);
+            self.snapshot.cells.iter().for_each(|c| bins.add(c));
+            bins.draw(cell_size, theme.alive);
+        } else if self.config.render_mode == RenderMode::Texture
+            && visible_top_right_cell.x - visible_top_left_cell.x < MAX_TEXTURE_CELLS
+            && visible_top_right_cell.y - visible_top_left_cell.y < MAX_TEXTURE_CELLS
+        {
+            let viewport = Region::new(
+                visible_top_left_cell.x,
+                visible_top_left_cell.y,
+                visible_top_right_cell.x - visible_top_left_cell.x + 1,
+                visible_top_right_cell.y - visible_top_left_cell.y + 1,
+            );
+            self.texture_renderer
+                .draw(&self.snapshot, viewport, cell_size, self.color_mode);
+        } else {
+            let is_visible = |c: &Cell| {
                 c.x >= visible_top_left_cell.x
                     && c.x <= visible_top_right_cell.x
                     && c.y >= visible_top_left_cell.y
                     && c.y <= visible_top_right_cell.y
-            })
-            .for_each(|c| {
+            };
+            self.snapshot
+                .heat
+                .iter()
+                .filter(|(c, _)| is_visible(c))
+                .for_each(|(c, heat)| {
+                    draw_rectangle(
+                        c.x as f32 * cell_size,
+                        c.y as f32 * cell_size,
+                        cell_size,
+                        cell_size,
+                        colors::heat_color(*heat),
+                    )
+                });
+            let draw_cell = |c: &Cell, color| {
                 draw_rectangle(
                     c.x as f32 * cell_size,
                     c.y as f32 * cell_size,
                     cell_size,
                     cell_size,
-                    BLACK,
+                    color,
                 )
-            });
+            };
+            colors::cell_colors(&self.snapshot, self.color_mode, theme.alive)
+                .filter(|(c, _)| is_visible(c))
+                .for_each(|(c, color)| draw_cell(c, color));
+            if self.color_mode == ColorMode::Plain
+                && let Some(step) = &self.snapshot.last_step
+                && step.revision == self.snapshot.revision
+            {
+                step.deaths
+                    .iter()
+                    .filter(|c| is_visible(c))
+                    .for_each(|c| draw_cell(c, theme.dying));
+                step.births
+                    .iter()
+                    .filter(|c| is_visible(c))
+                    .for_each(|c| draw_cell(c, theme.newborn));
+            }
+        }
 
+        if self.config.show_grid {
+            grid::draw_grid(
+                &visible_top_left_cell,
+                &visible_top_right_cell,
+                cell_size,
+                pixels_per_cell,
+                theme.grid,
+            );
+        }
         draw_rectangle_lines(
             0.0,
             0.0,
             self.config.cols as f32 * self.config.cell_size,
             self.config.rows as f32 * self.config.cell_size,
-            3.0 * cell_size,
-            BLACK,
+            2.0 * cell_size / pixels_per_cell,
+            theme.alive,
         );
 
+        self.selection.draw(cell_size);
+        if let Some(top_left_cell) = self.palette.placement(&self.cursor_cell()) {
+            self.palette.draw_preview(&top_left_cell, cell_size);
+        }
+
+        set_default_camera();
+        let visible_cells = Rect::new(
+            visible_top_left.x / cell_size,
+            visible_top_left.y / cell_size,
+            (visible_top_right.x - visible_top_left.x) / cell_size,
+            (visible_top_right.y - visible_top_left.y) / cell_size,
+        );
+        self.minimap
+            .draw(&self.snapshot, visible_cells, self.config.cols, self.config.rows);
+        self.hud.draw(&self.snapshot, 10.0, 80.0);
+
         root_ui().push_skin(&self.skin);
-        root_ui().label(
-            vec2(10.0, 10.0),
-            if self.is_running { "Running" } else { "Paused" },
+        let mut status = format!(
+            "{} | {} | {} colours",
+            if self.snapshot.is_running { "Running" } else { "Paused" },
+            self.snapshot.speed_label,
+            self.color_mode.name()
         );
+        if self.follow.is_active() {
+            status.push_str(" | following");
+        }
+        let cursor = self.cursor_cell();
+        status.push_str(&format!(" | x {} y {}", cursor.x, cursor.y));
+        if self.snapshot.is_budgeted {
+            status.push_str(&format!(
+                " | budget on, {} dropped",
+                self.snapshot.dropped_steps
+            ));
+        }
+        root_ui().label(vec2(10.0, 10.0), &status);
+        self.palette.draw_status(&mut root_ui(), vec2(10.0, 45.0));
         root_ui().pop_skin();
+        if let Some(message) = &self.status_message {
+            root_ui().label(vec2(10.0, screen_height() - 30.0), message);
+        }
+        self.palette.draw_window(screen_width());
+    }
+
+    fn cursor_cell(&self) -> Cell {
+        self.to_cell(self.camera.screen_to_world(mouse_position().into()))
     }
 
     fn to_cell(&self, pos: Vec2) -> Cell {
@@ -0,0 +1,175 @@
+use game_of_life_engine::Cell;
+use macroquad::color::Color;
+use macroquad::shapes::draw_rectangle;
+
+const MIN_BIN_ALPHA: f32 = 0.25;
+
+/// Alive cell counts aggregated into square bins of `bin_size`×`bin_size` cells,
+/// covering the rectangle between `top_left` and `bottom_right` (inclusive).
+#[derive(Clone)]
+pub struct DensityBins {
+    top_left: Cell,
+    bin_size: u32,
+    bins_cols: u32,
+    bins_rows: u32,
+    counts: Vec<u32>,
+}
+
+impl DensityBins {
+    pub fn new(top_left: Cell, bottom_right: &Cell, bin_size: u32) -> Self {
+        let bin_size = bin_size.max(1);
+        let bins_cols = (bottom_right.x - top_left.x) / bin_size + 1;
+        let bins_rows = (bottom_right.y - top_left.y) / bin_size + 1;
+        Self {
+            top_left,
+            bin_size,
+            bins_cols,
+            bins_rows,
+            counts: vec![0; bins_cols as usize * bins_rows as usize],
+        }
+    }
+
+    /// Counts `cell` into its bin. Cells outside of the covered rectangle are ignored.
+    pub fn add(&mut self, cell: &Cell) {
+        if let Some(index) = self.bin_index(cell) {
+            self.counts[index] += 1;
+        }
+    }
+
+    /// Takes `cell` back out of the count of its bin, e.g. when it dies.
+    pub fn remove(&mut self, cell: &Cell) {
+        if let Some(index) = self.bin_index(cell) {
+            self.counts[index] = self.counts[index].saturating_sub(1);
+        }
+    }
+
+    pub fn bin_size(&self) -> u32 {
+        self.bin_size
+    }
+
+    pub fn bins_cols(&self) -> u32 {
+        self.bins_cols
+    }
+
+    pub fn bins_rows(&self) -> u32 {
+        self.bins_rows
+    }
+
+    /// Iterates the share of alive cells of every bin, row by row.
+    pub fn densities(&self) -> impl Iterator<Item = f32> + '_ {
+        let bin_area = (self.bin_size * self.bin_size) as f32;
+        self.counts
+            .iter()
+            .map(move |count| *count as f32 / bin_area)
+    }
+
+    /// Iterates the non-empty bins as their top left cell and the share of their cells that are alive.
+    pub fn occupied(&self) -> impl Iterator<Item = (Cell, f32)> + '_ {
+        let bin_area = (self.bin_size * self.bin_size) as f32;
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(move |(index, count)| {
+                let bin_x = index as u32 % self.bins_cols;
+                let bin_y = index as u32 / self.bins_cols;
+                (
+                    Cell::new(
+                        self.top_left.x + bin_x * self.bin_size,
+                        self.top_left.y + bin_y * self.bin_size,
+                    ),
+                    *count as f32 / bin_area,
+                )
+            })
+    }
+
+    /// Draws one quad per non-empty bin in world coordinates, shaded by its density.
+    pub fn draw(&self, cell_size: f32, color: Color) {
+        let bin_world_size = self.bin_size as f32 * cell_size;
+        for (cell, density) in self.occupied() {
+            let alpha = MIN_BIN_ALPHA + (1.0 - MIN_BIN_ALPHA) * density.sqrt();
+            draw_rectangle(
+                cell.x as f32 * cell_size,
+                cell.y as f32 * cell_size,
+                bin_world_size,
+                bin_world_size,
+                Color { a: alpha, ..color },
+            );
+        }
+    }
+
+    fn bin_index(&self, cell: &Cell) -> Option<usize> {
+        if cell.x < self.top_left.x || cell.y < self.top_left.y {
+            return None;
+        }
+        let bin_x = (cell.x - self.top_left.x) / self.bin_size;
+        let bin_y = (cell.y - self.top_left.y) / self.bin_size;
+        (bin_x < self.bins_cols && bin_y < self.bins_rows)
+            .then(|| (bin_y * self.bins_cols + bin_x) as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_are_counted_in_their_bin() {
+        let mut bins = DensityBins::new(Cell::new(10, 20), &Cell::new(19, 29), 5);
+        assert_eq!((bins.bins_cols(), bins.bins_rows()), (2, 2));
+        for (x, y) in [(10, 20), (14, 24), (15, 20), (19, 29)] {
+            bins.add(&Cell::new(x, y));
+        }
+        let densities: Vec<f32> = bins.densities().collect();
+        assert_eq!(densities, [2.0 / 25.0, 1.0 / 25.0, 0.0, 1.0 / 25.0]);
+        let occupied: Vec<(Cell, f32)> = bins.occupied().collect();
+        assert_eq!(
+            occupied,
+            [
+                (Cell::new(10, 20), 2.0 / 25.0),
+                (Cell::new(15, 20), 1.0 / 25.0),
+                (Cell::new(15, 25), 1.0 / 25.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn edges_are_covered_by_partial_bins() {
+        // 12×7 cells do not split evenly into bins of 5, so the last column and row of bins
+        // only cover the remaining 2 columns and 2 rows.
+        let mut bins = DensityBins::new(Cell::new(0, 0), &Cell::new(11, 6), 5);
+        assert_eq!((bins.bins_cols(), bins.bins_rows()), (3, 2));
+        bins.add(&Cell::new(10, 5));
+        bins.add(&Cell::new(11, 6));
+        let occupied: Vec<(Cell, f32)> = bins.occupied().collect();
+        assert_eq!(occupied, [(Cell::new(10, 5), 2.0 / 25.0)]);
+    }
+
+    #[test]
+    fn cells_outside_the_covered_rectangle_are_ignored() {
+        let mut bins = DensityBins::new(Cell::new(5, 5), &Cell::new(9, 9), 5);
+        for (x, y) in [(4, 5), (5, 4), (10, 5), (5, 10)] {
+            bins.add(&Cell::new(x, y));
+        }
+        assert_eq!(bins.occupied().count(), 0);
+    }
+
+    #[test]
+    fn removed_cells_leave_their_bin() {
+        let mut bins = DensityBins::new(Cell::new(0, 0), &Cell::new(3, 3), 2);
+        bins.add(&Cell::new(1, 1));
+        bins.add(&Cell::new(0, 1));
+        bins.remove(&Cell::new(1, 1));
+        assert_eq!(bins.densities().collect::<Vec<_>>(), [0.25, 0.0, 0.0, 0.0]);
+        bins.remove(&Cell::new(0, 1));
+        bins.remove(&Cell::new(0, 1));
+        assert_eq!(bins.occupied().count(), 0);
+    }
+
+    #[test]
+    fn bins_are_at_least_one_cell() {
+        let bins = DensityBins::new(Cell::new(0, 0), &Cell::new(2, 1), 0);
+        assert_eq!(bins.bin_size(), 1);
+        assert_eq!((bins.bins_cols(), bins.bins_rows()), (3, 2));
+    }
+}
@@ -1,8 +1,79 @@
-use game_of_life_pixel::{Game, GameConfig, window_conf};
+use clap::Parser;
+use game_of_life_engine::Rule;
+use game_of_life_pixel::{ColorMode, Game, GameConfig, RenderMode, Theme, window_conf};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command()]
+pub struct Args {
+    /// Pattern file to start from, in RLE, plaintext or x,y format. If omitted, the world starts empty
+    #[arg(short, long)]
+    file: Option<PathBuf>,
+
+    /// Number of columns in the grid
+    #[arg(long, default_value = "5000", value_parser = clap::value_parser!(u32).range(1..))]
+    cols: u32,
+
+    /// Number of rows in the grid
+    #[arg(long, default_value = "5000", value_parser = clap::value_parser!(u32).range(1..))]
+    rows: u32,
+
+    /// Rule in B/S notation, e.g. B36/S23. Defaults to the pattern file's rule, or B3/S23
+    #[arg(short, long)]
+    rule: Option<Rule>,
+
+    /// How cells are drawn: shapes (one rectangle per cell) or texture (one uploaded image)
+    #[arg(long, default_value = "shapes")]
+    render: RenderMode,
+
+    /// What cell colours show: plain, age (how long cells have lived) or heat (recent activity)
+    #[arg(long, default_value = "plain")]
+    colors: ColorMode,
+
+    /// Colour theme: light, dark, or the path of a theme file
+    #[arg(long, default_value = "light")]
+    theme: String,
+
+    /// Directory where saved generations are written
+    #[arg(long, default_value = ".")]
+    save_dir: PathBuf,
+}
 
 #[macroquad::main(window_conf)]
 async fn main() {
-    let config = GameConfig::new(5000, 5000);
+    let args = Args::parse();
+    let theme = match Theme::named_or_load(&args.theme) {
+        Ok(theme) => theme,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let config = GameConfig::new(args.cols, args.rows)
+        .with_rule(args.rule)
+        .with_render_mode(args.render)
+        .with_color_mode(args.colors)
+        .with_theme(theme)
+        .with_save_directory(args.save_dir);
     let mut game = Game::new(config);
+    if let Some(path) = args.file
+        && let Err(e) = game.load_pattern_file(&path)
+    {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
     game.start().await;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_grids_are_rejected() {
+        assert!(Args::try_parse_from(["pixel", "--cols", "0"]).is_err());
+        assert!(Args::try_parse_from(["pixel", "--rows", "0"]).is_err());
+        let args = Args::try_parse_from(["pixel", "--cols", "1", "--rows", "1"]).unwrap();
+        assert_eq!((args.cols, args.rows), (1, 1));
+    }
+}
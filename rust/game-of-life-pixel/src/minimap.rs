@@ -0,0 +1,167 @@
+use crate::lod::DensityBins;
+use crate::simulation::Snapshot;
+use macroquad::color::{Color, WHITE};
+use macroquad::input::{
+    is_mouse_button_down, is_mouse_button_pressed, mouse_position, MouseButton,
+};
+use macroquad::math::{vec2, Rect, Vec2};
+use macroquad::shapes::{draw_rectangle, draw_rectangle_lines};
+use macroquad::texture::{draw_texture_ex, DrawTextureParams, FilterMode, Texture2D};
+use macroquad::window::{screen_height, screen_width};
+
+const BACKGROUND_COLOR: Color = Color::new(0.95, 0.95, 0.95, 0.9);
+const BORDER_COLOR: Color = Color::new(0.0, 0.0, 0.0, 1.0);
+const CAMERA_COLOR: Color = Color::new(1.0, 0.1, 0.1, 1.0);
+const MIN_BIN_ALPHA: f32 = 0.3;
+const MARGIN: f32 = 10.0;
+
+/// Overview of the whole world in a corner of the screen, drawn from the snapshot's density
+/// summary. Shows the area the camera looks at and moves the camera when clicked or dragged.
+pub struct Minimap {
+    is_visible: bool,
+    is_dragging: bool,
+    size: f32,
+    texture: Option<Texture2D>,
+    revision: Option<u64>,
+}
+
+impl Minimap {
+    /// Creates a minimap whose longer side is `size` pixels.
+    pub fn new(size: f32) -> Self {
+        Self {
+            is_visible: true,
+            is_dragging: false,
+            size,
+            texture: None,
+            revision: None,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.is_visible = !self.is_visible;
+    }
+
+    /// Returns whether `position`, in screen coordinates, is over the minimap.
+    pub fn contains(&self, position: Vec2, cols: u32, rows: u32) -> bool {
+        self.is_visible && self.rect(cols, rows).contains(position)
+    }
+
+    /// Returns the world position, in cells, the camera should move to while the
+    /// minimap is being clicked or dragged.
+    pub fn handle_input(&mut self, cols: u32, rows: u32) -> Option<Vec2> {
+        let mouse: Vec2 = mouse_position().into();
+        if is_mouse_button_pressed(MouseButton::Left) && self.contains(mouse, cols, rows) {
+            self.is_dragging = true;
+        }
+        if !is_mouse_button_down(MouseButton::Left) || !self.is_visible {
+            self.is_dragging = false;
+        }
+        if !self.is_dragging {
+            return None;
+        }
+        let rect = self.rect(cols, rows);
+        let scale = self.scale(cols, rows);
+        Some(vec2(
+            ((mouse.x - rect.x) / scale).clamp(0.0, cols as f32),
+            ((mouse.y - rect.y) / scale).clamp(0.0, rows as f32),
+        ))
+    }
+
+    /// Draws the minimap in screen coordinates, with `visible` being the area shown
+    /// by the camera, in cells.
+    pub fn draw(&mut self, snapshot: &Snapshot, visible: Rect, cols: u32, rows: u32) {
+        if !self.is_visible {
+            return;
+        }
+        let rect = self.rect(cols, rows);
+        let scale = self.scale(cols, rows);
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, BACKGROUND_COLOR);
+
+        let overview = &snapshot.overview;
+        if self.revision != Some(snapshot.revision) {
+            self.update_texture(overview);
+            self.revision = Some(snapshot.revision);
+        }
+        if let Some(texture) = &self.texture {
+            let bin_world_size = overview.bin_size() as f32 * scale;
+            // The last bins may reach past the edge of the world, so the texture is cut at it.
+            draw_texture_ex(
+                texture,
+                rect.x,
+                rect.y,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(rect.w, rect.h)),
+                    source: Some(Rect::new(
+                        0.0,
+                        0.0,
+                        rect.w / bin_world_size,
+                        rect.h / bin_world_size,
+                    )),
+                    ..Default::default()
+                },
+            );
+        }
+
+        let camera = Rect::new(
+            rect.x + visible.x.max(0.0) * scale,
+            rect.y + visible.y.max(0.0) * scale,
+            (visible.right().min(cols as f32) - visible.x.max(0.0)).max(0.0) * scale,
+            (visible.bottom().min(rows as f32) - visible.y.max(0.0)).max(0.0) * scale,
+        );
+        draw_rectangle_lines(
+            camera.x,
+            camera.y,
+            camera.w.max(2.0),
+            camera.h.max(2.0),
+            2.0,
+            CAMERA_COLOR,
+        );
+        draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, BORDER_COLOR);
+    }
+
+    fn update_texture(&mut self, overview: &DensityBins) {
+        let width = overview.bins_cols() as u16;
+        let height = overview.bins_rows() as u16;
+        let pixels: Vec<u8> = overview
+            .densities()
+            .flat_map(|density| {
+                if density > 0.0 {
+                    let alpha = MIN_BIN_ALPHA + (1.0 - MIN_BIN_ALPHA) * density.sqrt();
+                    [0, 0, 0, (alpha * 255.0) as u8]
+                } else {
+                    [0, 0, 0, 0]
+                }
+            })
+            .collect();
+        let texture = match self.texture.as_mut() {
+            Some(texture)
+                if texture.width() as u16 == width && texture.height() as u16 == height =>
+            {
+                texture.update_from_bytes(width as u32, height as u32, &pixels);
+                texture
+            }
+            _ => self
+                .texture
+                .insert(Texture2D::from_rgba8(width, height, &pixels)),
+        };
+        texture.set_filter(FilterMode::Nearest);
+    }
+
+    /// Pixels per cell.
+    fn scale(&self, cols: u32, rows: u32) -> f32 {
+        self.size / cols.max(rows) as f32
+    }
+
+    fn rect(&self, cols: u32, rows: u32) -> Rect {
+        let scale = self.scale(cols, rows);
+        let width = cols as f32 * scale;
+        let height = rows as f32 * scale;
+        Rect::new(
+            screen_width() - width - MARGIN,
+            screen_height() - height - MARGIN,
+            width,
+            height,
+        )
+    }
+}
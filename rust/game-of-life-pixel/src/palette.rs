@@ -0,0 +1,123 @@
+use crate::selection::is_ctrl_down;
+use game_of_life_engine::catalogue::CATALOGUE;
+use game_of_life_engine::{Cell, Pattern};
+use macroquad::color::Color;
+use macroquad::hash;
+use macroquad::input::{is_key_pressed, KeyCode};
+use macroquad::math::{vec2, Vec2};
+use macroquad::shapes::draw_rectangle;
+use macroquad::ui::widgets::Window;
+use macroquad::ui::{root_ui, Ui};
+
+const PREVIEW_COLOR: Color = Color::new(0.0, 0.6, 1.0, 0.45);
+const WINDOW_SIZE: Vec2 = vec2(220.0, 420.0);
+
+/// Pattern picker: lists the built-in catalogue and keeps the currently selected
+/// pattern, with the rotations and flips applied to it so far.
+pub struct Palette {
+    is_open: bool,
+    selected: Option<(&'static str, Pattern)>,
+}
+
+impl Palette {
+    pub fn new() -> Self {
+        Self {
+            is_open: false,
+            selected: None,
+        }
+    }
+
+    pub fn selected_pattern(&self) -> Option<&Pattern> {
+        self.selected.as_ref().map(|(_, pattern)| pattern)
+    }
+
+    /// Selects a pattern that is not part of the catalogue, e.g. the clipboard contents.
+    pub fn select(&mut self, name: &'static str, pattern: Pattern) {
+        self.selected = Some((name, pattern));
+    }
+
+    pub fn handle_input(&mut self) {
+        // Control shortcuts belong to the selection, e.g. Ctrl+V must not flip.
+        if is_ctrl_down() {
+            return;
+        }
+        if is_key_pressed(KeyCode::P) {
+            self.is_open = !self.is_open;
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            self.selected = None;
+        }
+        if let Some((_, pattern)) = self.selected.as_mut() {
+            if is_key_pressed(KeyCode::R) {
+                *pattern = pattern.rotate_clockwise();
+            }
+            if is_key_pressed(KeyCode::F) {
+                *pattern = pattern.flip_horizontal();
+            }
+            if is_key_pressed(KeyCode::V) {
+                *pattern = pattern.flip_vertical();
+            }
+        }
+    }
+
+    /// Returns the top left cell at which the selected pattern is placed when centered on `center`.
+    pub fn placement(&self, center: &Cell) -> Option<Cell> {
+        self.selected_pattern().map(|pattern| {
+            Cell::new(
+                center.x.saturating_sub(pattern.width() / 2),
+                center.y.saturating_sub(pattern.height() / 2),
+            )
+        })
+    }
+
+    /// Draws the ghost of the selected pattern in world coordinates.
+    pub fn draw_preview(&self, top_left: &Cell, cell_size: f32) {
+        if let Some(pattern) = self.selected_pattern() {
+            for cell in pattern.cells_at(top_left) {
+                draw_rectangle(
+                    cell.x as f32 * cell_size,
+                    cell.y as f32 * cell_size,
+                    cell_size,
+                    cell_size,
+                    PREVIEW_COLOR,
+                );
+            }
+        }
+    }
+
+    /// Draws the picker window, if open, in screen coordinates.
+    pub fn draw_window(&mut self, screen_width: f32) {
+        if !self.is_open {
+            return;
+        }
+        let position = vec2(screen_width - WINDOW_SIZE.x - 10.0, 10.0);
+        let mut picked = None;
+        Window::new(hash!(), position, WINDOW_SIZE)
+            .label("Patterns (P)")
+            .movable(false)
+            .ui(&mut root_ui(), |ui: &mut Ui| {
+                let mut category = None;
+                for entry in CATALOGUE {
+                    if category != Some(entry.category) {
+                        category = Some(entry.category);
+                        ui.label(None, entry.category.name());
+                    }
+                    if ui.button(None, entry.name) {
+                        picked = Some(entry);
+                    }
+                }
+                ui.separator();
+                ui.label(None, "R: rotate, F/V: flip");
+                ui.label(None, "Esc: drop selection");
+            });
+        if let Some(entry) = picked {
+            self.selected = Some((entry.name, entry.pattern()));
+        }
+    }
+
+    pub fn draw_status(&self, ui: &mut Ui, position: Vec2) {
+        if let Some((name, _)) = &self.selected {
+            ui.label(position, name);
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use crate::simulation::Snapshot;
+use game_of_life_engine::format::{PatternDocument, PatternFormat};
+use game_of_life_engine::{Cell, LifeEngine, Pattern, Rule};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Builds a new engine from a pattern file. Patterns without a position are centered in the grid.
+/// An explicitly configured `rule` takes precedence over the one stored in the file.
+pub fn load(path: &Path, cols: u32, rows: u32, rule: Option<Rule>) -> Result<LifeEngine, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Unable to read {}: {}", path.display(), e))?;
+    let document = PatternFormat::for_file(path, &text)
+        .parse(&text)
+        .map_err(|e| format!("Unable to parse {}: {}", path.display(), e))?;
+
+    let pattern = &document.pattern;
+    let origin = document.origin.clone().unwrap_or_else(|| {
+        Cell::new(
+            cols.saturating_sub(pattern.width()) / 2,
+            rows.saturating_sub(pattern.height()) / 2,
+        )
+    });
+    let fits = u64::from(origin.x) + u64::from(pattern.width()) <= u64::from(cols)
+        && u64::from(origin.y) + u64::from(pattern.height()) <= u64::from(rows);
+    if !fits {
+        return Err(format!(
+            "Pattern in {} does not fit in a {}x{} grid",
+            path.display(),
+            cols,
+            rows
+        ));
+    }
+
+    let mut engine = LifeEngine::new(cols, rows);
+    engine.set_rule(rule.or(document.rule).unwrap_or_default());
+    engine.stamp_pattern(pattern, origin);
+    Ok(engine)
+}
+
+/// Saves the snapshot's generation as RLE into a new timestamped file in `directory`.
+pub fn save(snapshot: &Snapshot, directory: &Path) -> Result<PathBuf, String> {
+    let document = PatternDocument {
+        pattern: Pattern::new(snapshot.cells.clone()),
+        origin: snapshot
+            .bounding_box
+            .as_ref()
+            .map(|(top_left, _)| top_left.clone()),
+        rule: Some(snapshot.rule),
+        name: None,
+    };
+
+    let path = timestamped_path(directory, "generation", PatternFormat::Rle.extension());
+    std::fs::write(&path, PatternFormat::Rle.write(&document))
+        .map_err(|e| format!("Unable to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Returns a new file path in `directory` named after the current time, e.g. `generation-<millis>.rle`.
+pub fn timestamped_path(directory: &Path, prefix: &str, extension: &str) -> PathBuf {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis());
+    directory.join(format!("{}-{}.{}", prefix, timestamp, extension))
+}
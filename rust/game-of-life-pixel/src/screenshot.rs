@@ -0,0 +1,68 @@
+use crate::colors::{cell_colors, heat_color};
+use crate::pattern_file::timestamped_path;
+use crate::simulation::Snapshot;
+use crate::theme::Theme;
+use crate::ColorMode;
+use game_of_life_engine::render::Frame;
+use game_of_life_engine::Region;
+use macroquad::color::Color;
+use std::path::{Path, PathBuf};
+
+/// Largest side, in pixels, of a saved image.
+const MAX_IMAGE_PIXELS: u32 = 8192;
+
+/// Saves the cells of `viewport` as a PNG image into a new timestamped file in `directory`,
+/// coloured as on screen. Cells are drawn `scale` pixels wide, or smaller if the image
+/// would otherwise be too large.
+pub fn save_view(
+    snapshot: &Snapshot,
+    viewport: Region,
+    scale: u32,
+    color_mode: ColorMode,
+    theme: &Theme,
+    directory: &Path,
+) -> Result<PathBuf, String> {
+    let longer_side = viewport.width.max(viewport.height);
+    if longer_side > MAX_IMAGE_PIXELS {
+        return Err(format!(
+            "Zoom in to save the view, images are at most {} cells wide",
+            MAX_IMAGE_PIXELS
+        ));
+    }
+    let scale = scale.clamp(1, MAX_IMAGE_PIXELS / longer_side);
+
+    let mut frame = Frame::new(viewport, scale, theme.background.into(), theme.alive.into())?;
+    let background = theme.background;
+    for (cell, heat) in &snapshot.heat {
+        frame.fill_cell(cell, over(heat_color(*heat), background).into());
+    }
+    for (cell, color) in cell_colors(snapshot, color_mode, theme.alive) {
+        frame.fill_cell(cell, over(color, background).into());
+    }
+    if color_mode == ColorMode::Plain
+        && let Some(step) = &snapshot.last_step
+        && step.revision == snapshot.revision
+    {
+        for cell in &step.deaths {
+            frame.fill_cell(cell, over(theme.dying, background).into());
+        }
+        for cell in &step.births {
+            frame.fill_cell(cell, over(theme.newborn, background).into());
+        }
+    }
+
+    let path = timestamped_path(directory, "view", "png");
+    frame.save_png(&path)?;
+    Ok(path)
+}
+
+/// Blends a translucent colour over an opaque background, as the screen would.
+fn over(color: Color, background: Color) -> Color {
+    let blend = |c: f32, b: f32| c * color.a + b * (1.0 - color.a);
+    Color::new(
+        blend(color.r, background.r),
+        blend(color.g, background.g),
+        blend(color.b, background.b),
+        1.0,
+    )
+}
@@ -0,0 +1,68 @@
+use game_of_life_engine::{Cell, Region};
+use macroquad::color::Color;
+use macroquad::input::{
+    is_key_down, is_key_pressed, is_mouse_button_down, is_mouse_button_pressed, KeyCode,
+    MouseButton,
+};
+use macroquad::shapes::{draw_rectangle, draw_rectangle_lines};
+
+const FILL_COLOR: Color = Color::new(1.0, 0.8, 0.0, 0.15);
+const OUTLINE_COLOR: Color = Color::new(1.0, 0.6, 0.0, 0.9);
+
+pub fn is_shift_down() -> bool {
+    is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift)
+}
+
+pub fn is_ctrl_down() -> bool {
+    is_key_down(KeyCode::LeftControl)
+        || is_key_down(KeyCode::RightControl)
+        || is_key_down(KeyCode::LeftSuper)
+        || is_key_down(KeyCode::RightSuper)
+}
+
+/// Rectangular selection of cells, made by dragging with Shift and the left mouse button.
+pub struct Selection {
+    anchor: Option<Cell>,
+    region: Option<Region>,
+}
+
+impl Selection {
+    pub fn new() -> Self {
+        Self {
+            anchor: None,
+            region: None,
+        }
+    }
+
+    pub fn region(&self) -> Option<&Region> {
+        self.region.as_ref()
+    }
+
+    pub fn handle_input(&mut self, cursor: Cell) {
+        if is_key_pressed(KeyCode::Escape) {
+            self.anchor = None;
+            self.region = None;
+        }
+        if is_shift_down() && is_mouse_button_pressed(MouseButton::Left) {
+            self.anchor = Some(cursor.clone());
+        }
+        if !is_mouse_button_down(MouseButton::Left) {
+            self.anchor = None;
+        }
+        if let Some(anchor) = &self.anchor {
+            self.region = Some(Region::from_corners(anchor, &cursor));
+        }
+    }
+
+    /// Draws the selected rectangle in world coordinates.
+    pub fn draw(&self, cell_size: f32) {
+        if let Some(region) = &self.region {
+            let x = region.x as f32 * cell_size;
+            let y = region.y as f32 * cell_size;
+            let width = region.width as f32 * cell_size;
+            let height = region.height as f32 * cell_size;
+            draw_rectangle(x, y, width, height, FILL_COLOR);
+            draw_rectangle_lines(x, y, width, height, cell_size.max(1.0), OUTLINE_COLOR);
+        }
+    }
+}
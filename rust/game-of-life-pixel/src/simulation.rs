@@ -0,0 +1,324 @@
+use crate::lod::DensityBins;
+use crate::speed::Scheduler;
+use game_of_life_engine::history::History;
+use game_of_life_engine::{Cell, LifeEngine, Pattern, Region, Rule};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the worker publishes a snapshot while running.
+const TICK: Duration = Duration::from_micros(16_667);
+
+/// Requests sent from the UI thread to the simulation worker.
+pub enum Command {
+    ToggleRunning,
+    /// Computes the given number of generations right away, whether running or not.
+    Step(u32),
+    Faster,
+    Slower,
+    ToggleBudget,
+    Stamp(Pattern, Cell),
+    RandomSquare(Cell, u32),
+    ClearRegion(Region),
+    InvertRegion(Region),
+    /// Replaces the region with random cells of the given density.
+    FillRegion(Region, f64),
+    SetAgeTracking(bool),
+    /// Enables the heat map with the given decay per generation, or disables it.
+    SetHeatMap(Option<f32>),
+    Undo,
+    Redo,
+    /// Pauses and goes back one generation.
+    StepBack,
+    /// Replaces the whole world, e.g. with one loaded from a file.
+    Replace(Box<LifeEngine>),
+}
+
+/// Births, deaths and duration of a single step.
+pub struct StepSummary {
+    /// Revision of the snapshot the step produced.
+    pub revision: u64,
+    pub births: Vec<Cell>,
+    pub deaths: Vec<Cell>,
+    pub elapsed: Duration,
+}
+
+/// Immutable copy of the world published by the worker for the UI to draw.
+pub struct Snapshot {
+    /// Increases with every step and every edit, so that consecutive
+    /// revisions differ by exactly one change.
+    pub revision: u64,
+    pub generation: u64,
+    pub rule: Rule,
+    pub cells: Vec<Cell>,
+    /// Age of each cell in `cells`, in the same order. Empty unless age tracking is on.
+    pub cell_ages: Vec<u64>,
+    /// Warm cells of the heat map. Empty unless the heat map is on.
+    pub heat: Vec<(Cell, f32)>,
+    pub bounding_box: Option<(Cell, Cell)>,
+    /// Alive cell counts of the whole world at low resolution.
+    pub overview: DensityBins,
+    pub last_step: Option<StepSummary>,
+    /// Population after each step computed since the previous snapshot the UI received.
+    pub step_populations: Vec<usize>,
+    pub is_running: bool,
+    pub speed_label: String,
+    pub is_budgeted: bool,
+    pub dropped_steps: u64,
+}
+
+/// Handle to the simulation worker thread. The engine lives on the worker, which
+/// steps it at the selected speed and publishes the latest state as a `Snapshot`;
+/// the UI only ever reads snapshots and talks to the engine through `Command`s.
+/// The worker stops when the handle is dropped.
+pub struct Simulation {
+    commands: Sender<Command>,
+    latest: Arc<Mutex<Option<Snapshot>>>,
+}
+
+impl Simulation {
+    /// Starts the worker. The overview of the world is kept with about
+    /// `overview_resolution` bins along its longer side.
+    pub fn spawn(engine: LifeEngine, scheduler: Scheduler, overview_resolution: u32) -> Self {
+        let (commands, receiver) = channel();
+        let latest = Arc::new(Mutex::new(None));
+        let mut worker = Worker {
+            overview: overview_of(&engine, overview_resolution),
+            overview_resolution,
+            engine,
+            history: History::new(),
+            scheduler,
+            is_running: false,
+            revision: 0,
+            last_step: None,
+            step_populations: Vec::new(),
+            is_outdated: true,
+            commands: receiver,
+            latest: Arc::clone(&latest),
+        };
+        worker.publish();
+        thread::spawn(move || worker.run());
+        Self { commands, latest }
+    }
+
+    pub fn send(&self, command: Command) {
+        // The worker only goes away together with the handle, so this cannot fail.
+        let _ = self.commands.send(command);
+    }
+
+    /// Takes the snapshot published since the last call, if there is one.
+    pub fn take_snapshot(&self) -> Option<Snapshot> {
+        self.latest.lock().ok()?.take()
+    }
+}
+
+struct Worker {
+    engine: LifeEngine,
+    history: History,
+    /// Kept up to date with the births and deaths of every step, and rebuilt after edits.
+    overview: DensityBins,
+    overview_resolution: u32,
+    scheduler: Scheduler,
+    is_running: bool,
+    revision: u64,
+    last_step: Option<StepSummary>,
+    step_populations: Vec<usize>,
+    /// Whether the world changed since the last published snapshot.
+    is_outdated: bool,
+    commands: Receiver<Command>,
+    latest: Arc<Mutex<Option<Snapshot>>>,
+}
+
+impl Worker {
+    fn run(mut self) {
+        let mut last_tick = Instant::now();
+        loop {
+            // While paused, the worker only wakes up to publish changes the UI hasn't picked up yet.
+            let timeout = if self.is_running {
+                Some(TICK.saturating_sub(last_tick.elapsed()))
+            } else if self.is_outdated {
+                Some(TICK)
+            } else {
+                None
+            };
+            let command = if let Some(timeout) = timeout {
+                match self.commands.recv_timeout(timeout) {
+                    Ok(command) => Some(command),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            } else {
+                match self.commands.recv() {
+                    Ok(command) => Some(command),
+                    Err(_) => return,
+                }
+            };
+
+            match command {
+                Some(command) => {
+                    if matches!(command, Command::ToggleRunning) && !self.is_running {
+                        last_tick = Instant::now();
+                        self.scheduler.reset();
+                    }
+                    let revision = self.revision;
+                    let is_step = matches!(command, Command::Step(_));
+                    self.handle(command);
+                    if !is_step && self.revision != revision {
+                        self.overview = overview_of(&self.engine, self.overview_resolution);
+                    }
+                    self.is_outdated = true;
+                }
+                None if self.is_running => {
+                    self.scheduler
+                        .begin_frame(last_tick.elapsed().as_secs_f32());
+                    last_tick = Instant::now();
+                    while self.scheduler.next_step_due() {
+                        self.step();
+                    }
+                    self.is_outdated = true;
+                }
+                None => {}
+            }
+            self.publish();
+        }
+    }
+
+    fn handle(&mut self, command: Command) {
+        match command {
+            Command::ToggleRunning => self.is_running = !self.is_running,
+            Command::Step(count) => {
+                for _ in 0..count {
+                    self.step();
+                }
+            }
+            Command::Faster => self.scheduler.faster(),
+            Command::Slower => self.scheduler.slower(),
+            Command::ToggleBudget => self.scheduler.toggle_budget(),
+            Command::Stamp(pattern, top_left) => self.edit(|engine| {
+                engine.stamp_pattern(&pattern, top_left);
+            }),
+            Command::RandomSquare(top_left, size) => self.edit(|engine| {
+                engine.generate_random_square(top_left, size);
+            }),
+            Command::ClearRegion(region) => self.edit(|engine| engine.clear_region(&region)),
+            Command::InvertRegion(region) => self.edit(|engine| engine.invert_region(&region)),
+            Command::FillRegion(region, density) => {
+                self.edit(|engine| engine.fill_region_randomly(&region, density))
+            }
+            Command::SetAgeTracking(is_enabled) => {
+                self.engine.set_age_tracking(is_enabled);
+                self.revision += 1;
+            }
+            Command::SetHeatMap(decay) => {
+                if self.engine.set_heat_map(decay).is_ok() {
+                    self.revision += 1;
+                }
+            }
+            Command::Undo => {
+                if self.history.undo(&mut self.engine) {
+                    self.revision += 1;
+                }
+            }
+            Command::Redo => {
+                if self.history.redo(&mut self.engine) {
+                    self.revision += 1;
+                }
+            }
+            Command::StepBack => {
+                self.is_running = false;
+                if self.history.step_back(&mut self.engine) {
+                    self.revision += 1;
+                }
+            }
+            Command::Replace(engine) => {
+                let is_age_tracking = self.engine.is_age_tracking();
+                let heat_decay = self.engine.get_heat_map().map(|h| h.get_decay());
+                self.engine = *engine;
+                self.engine.set_age_tracking(is_age_tracking);
+                // The decay was accepted by the previous engine.
+                let _ = self.engine.set_heat_map(heat_decay);
+                self.history.clear();
+                self.revision += 1;
+            }
+        }
+    }
+
+    fn edit(&mut self, edit: impl FnOnce(&mut LifeEngine)) {
+        self.history.edit(&mut self.engine, edit);
+        self.revision += 1;
+    }
+
+    fn step(&mut self) {
+        let instant = Instant::now();
+        self.history.step(&mut self.engine);
+        self.revision += 1;
+        for cell in self.engine.get_last_deaths() {
+            self.overview.remove(cell);
+        }
+        for cell in self.engine.get_last_births() {
+            self.overview.add(cell);
+        }
+        self.last_step = Some(StepSummary {
+            revision: self.revision,
+            births: self.engine.get_last_births().to_vec(),
+            deaths: self.engine.get_last_deaths().to_vec(),
+            elapsed: instant.elapsed(),
+        });
+        self.step_populations
+            .push(self.engine.get_alive_cells_count());
+    }
+
+    /// Publishes the current state, unless the UI hasn't taken the previous snapshot yet:
+    /// copying the world is then left for a later tick rather than thrown away.
+    fn publish(&mut self) {
+        // Only the UI empties the slot, so it is still empty once the copy below is made,
+        // which happens without holding the lock.
+        let is_taken = self.latest.lock().is_ok_and(|latest| latest.is_none());
+        if !self.is_outdated || !is_taken {
+            return;
+        }
+        let cells: Vec<Cell> = self.engine.get_alive_cells().cloned().collect();
+        let cell_ages = if self.engine.is_age_tracking() {
+            cells
+                .iter()
+                .map(|c| self.engine.get_cell_age(c).unwrap_or(0))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let heat = self
+            .engine
+            .get_heat_map()
+            .map(|heat_map| heat_map.iter().map(|(c, h)| (c.clone(), h)).collect())
+            .unwrap_or_default();
+        let snapshot = Snapshot {
+            revision: self.revision,
+            generation: self.engine.get_generation(),
+            rule: self.engine.get_rule(),
+            cells,
+            cell_ages,
+            heat,
+            bounding_box: self.engine.get_bounding_box(),
+            overview: self.overview.clone(),
+            last_step: self.last_step.take(),
+            step_populations: std::mem::take(&mut self.step_populations),
+            is_running: self.is_running,
+            speed_label: self.scheduler.speed_label(),
+            is_budgeted: self.scheduler.is_budgeted(),
+            dropped_steps: self.scheduler.dropped_steps(),
+        };
+        if let Ok(mut latest) = self.latest.lock() {
+            *latest = Some(snapshot);
+        }
+        self.is_outdated = false;
+    }
+}
+
+fn overview_of(engine: &LifeEngine, resolution: u32) -> DensityBins {
+    let bottom_right = Cell::new(engine.get_cols() - 1, engine.get_rows() - 1);
+    let bin_size = (bottom_right.x.max(bottom_right.y) + 1).div_ceil(resolution.max(1));
+    let mut overview = DensityBins::new(Cell::new(0, 0), &bottom_right, bin_size);
+    engine.get_alive_cells().for_each(|c| overview.add(c));
+    overview
+}
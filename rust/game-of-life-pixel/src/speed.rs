@@ -0,0 +1,175 @@
+use std::time::{Duration, Instant};
+
+/// Selectable simulation speeds in generations per second. `None` means unlimited.
+const SPEEDS: [Option<f32>; 12] = [
+    Some(1.0),
+    Some(2.0),
+    Some(5.0),
+    Some(10.0),
+    Some(20.0),
+    Some(50.0),
+    Some(100.0),
+    Some(200.0),
+    Some(500.0),
+    Some(1000.0),
+    Some(5000.0),
+    None,
+];
+
+/// Decides how many generations to compute each frame for the selected speed.
+/// A frame here is one tick of the simulation worker, which publishes a snapshot after it.
+///
+/// With the frame budget enabled, stepping stops once the budget is spent and the
+/// generations that were due but not computed are dropped instead of being carried
+/// over, so a slow step can never snowball into ever growing catch-up work.
+/// Unlimited speed always runs within the budget.
+pub struct Scheduler {
+    speed_index: usize,
+    frame_budget: Duration,
+    is_budgeted: bool,
+    frame_start: Instant,
+    accumulator: f32,
+    dropped_steps: u64,
+}
+
+impl Scheduler {
+    /// Creates a scheduler starting at the predefined speed closest to `generations_per_second`.
+    pub fn new(generations_per_second: f32, frame_budget: Duration) -> Self {
+        let speed_index = SPEEDS
+            .iter()
+            .enumerate()
+            .filter_map(|(i, speed)| speed.map(|s| (i, (s - generations_per_second).abs())))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(0, |(i, _)| i);
+        Self {
+            speed_index,
+            frame_budget,
+            is_budgeted: true,
+            frame_start: Instant::now(),
+            accumulator: 0.0,
+            dropped_steps: 0,
+        }
+    }
+
+    pub fn faster(&mut self) {
+        self.speed_index = (self.speed_index + 1).min(SPEEDS.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed_index = self.speed_index.saturating_sub(1);
+    }
+
+    pub fn toggle_budget(&mut self) {
+        self.is_budgeted = !self.is_budgeted;
+    }
+
+    pub fn is_budgeted(&self) -> bool {
+        self.is_budgeted
+    }
+
+    /// Generations that were due but skipped to stay within the frame budget.
+    pub fn dropped_steps(&self) -> u64 {
+        self.dropped_steps
+    }
+
+    /// Forgets the time accumulated so far, e.g. when the simulation is resumed.
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+    }
+
+    pub fn speed_label(&self) -> String {
+        match SPEEDS[self.speed_index] {
+            Some(speed) => format!("{} gen/s", speed),
+            None => String::from("unlimited"),
+        }
+    }
+
+    /// Starts a new frame, `frame_time` seconds after the previous one.
+    pub fn begin_frame(&mut self, frame_time: f32) {
+        self.frame_start = Instant::now();
+        self.accumulator += frame_time;
+    }
+
+    /// Returns whether another generation should be computed during the current frame.
+    pub fn next_step_due(&mut self) -> bool {
+        let is_over_budget = self.frame_start.elapsed() >= self.frame_budget;
+        let Some(speed) = SPEEDS[self.speed_index] else {
+            self.accumulator = 0.0;
+            return !is_over_budget;
+        };
+        let interval = 1.0 / speed;
+        if self.accumulator < interval {
+            return false;
+        }
+        if self.is_budgeted && is_over_budget {
+            let dropped = (self.accumulator / interval).floor();
+            self.dropped_steps += dropped as u64;
+            self.accumulator -= dropped * interval;
+            return false;
+        }
+        self.accumulator -= interval;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AMPLE_BUDGET: Duration = Duration::from_secs(3600);
+
+    fn steps_due(scheduler: &mut Scheduler, frame_time: f32) -> u32 {
+        scheduler.begin_frame(frame_time);
+        let mut steps = 0;
+        while scheduler.next_step_due() {
+            steps += 1;
+        }
+        steps
+    }
+
+    #[test]
+    fn steps_follow_the_elapsed_time() {
+        let mut scheduler = Scheduler::new(2.0, AMPLE_BUDGET);
+        assert_eq!(scheduler.speed_label(), "2 gen/s");
+        assert_eq!(steps_due(&mut scheduler, 1.75), 3);
+        // The remaining quarter of a step is carried over to the next frame.
+        assert_eq!(steps_due(&mut scheduler, 0.25), 1);
+        assert_eq!(steps_due(&mut scheduler, 0.25), 0);
+        assert_eq!(scheduler.dropped_steps(), 0);
+    }
+
+    #[test]
+    fn steps_over_the_budget_are_dropped() {
+        let mut scheduler = Scheduler::new(2.0, Duration::ZERO);
+        assert_eq!(steps_due(&mut scheduler, 1.75), 0);
+        assert_eq!(scheduler.dropped_steps(), 3);
+        // Only the part of a step that was not due yet is carried over.
+        assert_eq!(steps_due(&mut scheduler, 0.25), 0);
+        assert_eq!(scheduler.dropped_steps(), 4);
+    }
+
+    #[test]
+    fn steps_are_kept_without_the_budget() {
+        let mut scheduler = Scheduler::new(2.0, Duration::ZERO);
+        scheduler.toggle_budget();
+        assert!(!scheduler.is_budgeted());
+        assert_eq!(steps_due(&mut scheduler, 1.75), 3);
+        assert_eq!(scheduler.dropped_steps(), 0);
+    }
+
+    #[test]
+    fn unlimited_speed_runs_until_the_budget_is_spent() {
+        let mut scheduler = Scheduler::new(5000.0, Duration::ZERO);
+        scheduler.faster();
+        assert_eq!(scheduler.speed_label(), "unlimited");
+        assert_eq!(steps_due(&mut scheduler, 0.0), 0);
+        scheduler.toggle_budget();
+        assert_eq!(steps_due(&mut scheduler, 0.0), 0);
+
+        let mut scheduler = Scheduler::new(5000.0, AMPLE_BUDGET);
+        scheduler.faster();
+        scheduler.begin_frame(0.0);
+        assert!((0..100).all(|_| scheduler.next_step_due()));
+        assert_eq!(scheduler.dropped_steps(), 0);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::colors::{cell_colors, heat_color};
+use crate::simulation::Snapshot;
+use crate::theme::Theme;
+use crate::ColorMode;
+use game_of_life_engine::render::{Frame, Rgba};
+use game_of_life_engine::{Cell, Region};
+use macroquad::color::WHITE;
+use macroquad::math::vec2;
+use macroquad::texture::{draw_texture_ex, DrawTextureParams, FilterMode, Texture2D};
+
+/// The background is left transparent, it is cleared to the theme's colour before drawing.
+const BACKGROUND: Rgba = [0, 0, 0, 0];
+
+/// Draws the visible cells by rasterising them into a CPU side frame, one pixel per cell,
+/// and uploading it as a single texture. The frame is kept between draws and, while the
+/// viewport stays the same, updated from the births and deaths of single steps.
+/// Age and heat colours change with every step, so in those modes each new snapshot is redrawn.
+pub struct TextureRenderer {
+    frame: Option<Frame>,
+    texture: Option<Texture2D>,
+    revision: Option<u64>,
+    color_mode: ColorMode,
+    theme: Theme,
+    /// Births and deaths currently drawn in the newborn and dying colours.
+    highlighted: Option<(Vec<Cell>, Vec<Cell>)>,
+}
+
+impl TextureRenderer {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            frame: None,
+            texture: None,
+            revision: None,
+            color_mode: ColorMode::Plain,
+            theme,
+            highlighted: None,
+        }
+    }
+
+    /// Draws the viewport in world coordinates.
+    pub fn draw(
+        &mut self,
+        snapshot: &Snapshot,
+        viewport: Region,
+        cell_size: f32,
+        color_mode: ColorMode,
+    ) {
+        let frame = match self.frame.as_mut() {
+            Some(frame) if *frame.viewport() == viewport && self.color_mode == color_mode => frame,
+            _ => {
+                self.revision = None;
+                // Visible regions drawn as a texture are small, so this only fails without memory.
+                let Ok(frame) = Frame::new(viewport, 1, BACKGROUND, self.theme.alive.into()) else {
+                    return;
+                };
+                self.frame.insert(frame)
+            }
+        };
+        let next_step = snapshot
+            .last_step
+            .as_ref()
+            .filter(|step| step.revision == snapshot.revision);
+        match (self.revision, next_step) {
+            (Some(revision), _) if revision == snapshot.revision => {}
+            (Some(revision), Some(_))
+                if revision + 1 == snapshot.revision && color_mode == ColorMode::Plain =>
+            {
+                if let Some((births, deaths)) = self.highlighted.take() {
+                    frame.apply_changes(&births, &deaths);
+                }
+            }
+            _ if color_mode == ColorMode::Plain => {
+                self.highlighted = None;
+                frame.draw_cells(&snapshot.cells);
+            }
+            _ => {
+                frame.clear();
+                for (cell, heat) in &snapshot.heat {
+                    frame.fill_cell(cell, heat_color(*heat).into());
+                }
+                for (cell, color) in cell_colors(snapshot, color_mode, self.theme.alive) {
+                    frame.fill_cell(cell, color.into());
+                }
+            }
+        }
+        if color_mode == ColorMode::Plain
+            && self.revision != Some(snapshot.revision)
+            && let Some(step) = next_step
+        {
+            for cell in &step.deaths {
+                frame.fill_cell(cell, self.theme.dying.into());
+            }
+            for cell in &step.births {
+                frame.fill_cell(cell, self.theme.newborn.into());
+            }
+            self.highlighted = Some((step.births.clone(), step.deaths.clone()));
+        }
+        self.revision = Some(snapshot.revision);
+        self.color_mode = color_mode;
+
+        let width = frame.width() as u16;
+        let height = frame.height() as u16;
+        let texture = match self.texture.as_mut() {
+            Some(texture)
+                if texture.width() as u16 == width && texture.height() as u16 == height =>
+            {
+                texture.update_from_bytes(width as u32, height as u32, frame.pixels());
+                texture
+            }
+            _ => self
+                .texture
+                .insert(Texture2D::from_rgba8(width, height, frame.pixels())),
+        };
+        texture.set_filter(FilterMode::Nearest);
+
+        draw_texture_ex(
+            texture,
+            viewport.x as f32 * cell_size,
+            viewport.y as f32 * cell_size,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(
+                    viewport.width as f32 * cell_size,
+                    viewport.height as f32 * cell_size,
+                )),
+                ..Default::default()
+            },
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use game_of_life_engine::render;
+use macroquad::color::Color;
+use std::path::Path;
+
+/// Colours the world is drawn with.
+///
+/// A theme is either one of the built-in ones or loaded from a file of `key = value` lines,
+/// where keys are `background`, `alive`, `newborn`, `dying` and `grid` and values are
+/// `#rrggbb` or `#rrggbbaa` colours. A `base = light` or `base = dark` line picks the
+/// theme the unset colours are taken from, light by default. Lines starting with `#` are comments.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Theme {
+    pub background: Color,
+    pub alive: Color,
+    /// Cells born during the last step.
+    pub newborn: Color,
+    /// Cells that died during the last step.
+    pub dying: Color,
+    pub grid: Color,
+}
+
+impl Theme {
+    pub const LIGHT: Theme = Theme {
+        background: Color::new(1.0, 1.0, 1.0, 1.0),
+        alive: Color::new(0.0, 0.0, 0.0, 1.0),
+        newborn: Color::new(0.0, 0.55, 0.25, 1.0),
+        dying: Color::new(0.85, 0.2, 0.2, 0.5),
+        grid: Color::new(0.0, 0.0, 0.0, 0.12),
+    };
+
+    pub const DARK: Theme = Theme {
+        background: Color::new(0.02, 0.024, 0.04, 1.0),
+        alive: Color::new(0.29, 1.0, 0.65, 1.0),
+        newborn: Color::new(1.0, 1.0, 1.0, 1.0),
+        dying: Color::new(1.0, 0.3, 0.3, 0.5),
+        grid: Color::new(1.0, 1.0, 1.0, 0.08),
+    };
+
+    /// Returns the built-in theme with the given name.
+    pub fn named(name: &str) -> Option<Theme> {
+        match name.to_ascii_lowercase().as_str() {
+            "light" => Some(Theme::LIGHT),
+            "dark" => Some(Theme::DARK),
+            _ => None,
+        }
+    }
+
+    /// Returns the built-in theme called `name`, or else loads the theme file at that path.
+    pub fn named_or_load(name: &str) -> Result<Theme, String> {
+        Theme::named(name).map_or_else(|| Theme::load(Path::new(name)), Ok)
+    }
+
+    pub fn load(path: &Path) -> Result<Theme, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read theme {}: {}", path.display(), e))?;
+        Theme::parse(&text).map_err(|e| format!("Invalid theme {}: {}", path.display(), e))
+    }
+
+    pub fn parse(text: &str) -> Result<Theme, String> {
+        let entries = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(number, line)| {
+                line.split_once('=')
+                    .map(|(key, value)| (number, key.trim(), value.trim()))
+                    .ok_or_else(|| format!("line {}: expected key = value", number))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut theme = Theme::LIGHT;
+        if let Some((number, _, base)) = entries.iter().find(|(_, key, _)| *key == "base") {
+            theme = Theme::named(base)
+                .ok_or_else(|| format!("line {}: unknown base theme '{}'", number, base))?;
+        }
+        for (number, key, value) in entries {
+            let slot = match key {
+                "base" => continue,
+                "background" => &mut theme.background,
+                "alive" => &mut theme.alive,
+                "newborn" => &mut theme.newborn,
+                "dying" => &mut theme.dying,
+                "grid" => &mut theme.grid,
+                _ => return Err(format!("line {}: unknown key '{}'", number, key)),
+            };
+            *slot = parse_color(value).map_err(|e| format!("line {}: {}", number, e))?;
+        }
+        Ok(theme)
+    }
+}
+
+fn parse_color(value: &str) -> Result<Color, String> {
+    let [r, g, b, a] = render::parse_color(value)?;
+    Ok(Color::from_rgba(r, g, b, a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_themes_are_found_by_name() {
+        assert_eq!(Theme::named("Dark"), Some(Theme::DARK));
+        assert_eq!(Theme::named_or_load("light"), Ok(Theme::LIGHT));
+        assert_eq!(Theme::named("sepia"), None);
+    }
+
+    #[test]
+    fn unset_colours_come_from_the_base_theme() {
+        assert_eq!(Theme::parse(""), Ok(Theme::LIGHT));
+        let text = "# Dark with red cells\n\nalive = #ff0000\n  base = dark  \ngrid=#ffffff80\n";
+        let theme = Theme::parse(text).unwrap();
+        assert_eq!(theme.alive, Color::from_rgba(255, 0, 0, 255));
+        assert_eq!(theme.grid, Color::from_rgba(255, 255, 255, 128));
+        assert_eq!(theme.background, Theme::DARK.background);
+        assert_eq!(theme.newborn, Theme::DARK.newborn);
+    }
+
+    #[test]
+    fn invalid_lines_are_reported_with_their_number() {
+        let error = |text: &str| Theme::parse(text).unwrap_err();
+        assert_eq!(error("# comment\nalive"), "line 2: expected key = value");
+        assert_eq!(error("base = sepia"), "line 1: unknown base theme 'sepia'");
+        assert_eq!(error("\ncolour = #000000"), "line 2: unknown key 'colour'");
+        assert!(error("alive = #000000\ndying = red").starts_with("line 2: invalid colour 'red'"));
+        assert!(error("alive = #12345").starts_with("line 1: invalid colour"));
+    }
+
+    #[test]
+    fn missing_theme_files_are_reported() {
+        let error = Theme::named_or_load("no/such/theme.txt").unwrap_err();
+        assert!(error.starts_with("Unable to read theme"), "{}", error);
+    }
+}
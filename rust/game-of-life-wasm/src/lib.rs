@@ -1,12 +1,41 @@
-use game_of_life_engine::{Cell, LifeEngine};
-use js_sys::{Array, Function, Number, Uint32Array};
+use game_of_life_engine::animation::{self, GifCrop, GifOptions};
+use game_of_life_engine::format::{self, PatternDocument};
+use game_of_life_engine::render::{Frame, Rgba, parse_color};
+use game_of_life_engine::share;
+use game_of_life_engine::{Cell, LifeEngine, Region};
+use js_sys::{Function, Number, Uint8ClampedArray, Uint32Array};
 use wasm_bindgen::prelude::*;
 
 /* ===== WASM surface for JS/TS ===== */
 
+#[wasm_bindgen]
+extern "C" {
+    // performance.now(): milliseconds from a monotonic clock with sub-millisecond resolution,
+    // unlike Date.now(), which browsers round and which jumps when the system clock is set.
+    #[wasm_bindgen(js_namespace = performance, js_name = now)]
+    fn performance_now() -> f64;
+}
+
 #[wasm_bindgen]
 pub struct LifeEngineWrapper {
     engine: LifeEngine,
+    // Bumped on every change of the engine, to tell when the buffers below are out of date.
+    revision: u64,
+    // Revision the last call to next() produced.
+    step_revision: u64,
+    // Alive cells as x, y pairs, rebuilt on demand after the engine changed.
+    alive_cells: Vec<u32>,
+    alive_cells_revision: Option<u64>,
+    // Last rasterised viewport, with the settings and the revision it was drawn with.
+    frame: Option<(Frame, FrameSettings, u64)>,
+}
+
+#[derive(PartialEq, Eq)]
+struct FrameSettings {
+    viewport: Region,
+    scale: u32,
+    background: Rgba,
+    alive: Rgba,
 }
 
 #[wasm_bindgen]
@@ -14,21 +43,51 @@ impl LifeEngineWrapper {
     // Create a new Engine and return an opaque pointer to it.
     #[wasm_bindgen(constructor)]
     pub fn new(cols: u32, rows: u32) -> LifeEngineWrapper {
-        LifeEngineWrapper {
-            engine: LifeEngine::new(cols, rows),
-        }
+        LifeEngineWrapper::with_engine(LifeEngine::new(cols, rows))
+    }
+
+    // Create an Engine from a string written by encode_state, e.g. kept in the page URL.
+    #[wasm_bindgen]
+    pub fn from_state(text: &str) -> Result<LifeEngineWrapper, String> {
+        share::decode(text).map(LifeEngineWrapper::with_engine)
+    }
+
+    // Encodes the grid size, rule, generation and alive cells into a compact URL-safe string.
+    // Fails for grids larger than from_state accepts.
+    #[wasm_bindgen]
+    pub fn encode_state(&self) -> Result<String, String> {
+        share::encode(&self.engine)
+    }
+
+    #[wasm_bindgen]
+    pub fn get_cols(&self) -> u32 {
+        self.engine.get_cols()
+    }
+
+    #[wasm_bindgen]
+    pub fn get_rows(&self) -> u32 {
+        self.engine.get_rows()
     }
 
     // Advance the engine by one tick.
     #[wasm_bindgen]
     pub fn next(&mut self) {
-        self.engine.next();
+        self.engine_mut().next();
+        self.step_revision = self.revision;
+    }
+
+    // Runs up to `max_steps` generations, stopping once `budget_ms` milliseconds are spent,
+    // and returns how many ran. The budget is checked after every step, so the first step always
+    // runs and the last one may overrun it; meant to be called once per animation frame.
+    #[wasm_bindgen]
+    pub fn tick(&mut self, budget_ms: f64, max_steps: u32) -> u32 {
+        self.run_for(budget_ms, max_steps, performance_now)
     }
 
     // Activate a cell at (x, y).
     #[wasm_bindgen]
     pub fn activate_cell(&mut self, x: u32, y: u32) -> Result<(), String> {
-        self.engine.activate_cell(x, y)
+        self.engine_mut().activate_cell(x, y)
     }
 
     #[wasm_bindgen]
@@ -42,22 +101,133 @@ impl LifeEngineWrapper {
             let y = cells_y.get_index(i);
             cells.push(Cell::new(x, y));
         }
-        self.engine.activate_cells(&cells)
+        self.engine_mut().activate_cells(&cells)
     }
 
     // Generate a random square of cells.
     #[wasm_bindgen]
     pub fn generate_random_square(&mut self, top_left_x: u32, top_left_y: u32, size: u32) {
-        self.engine
+        self.engine_mut()
             .generate_random_square(Cell::new(top_left_x, top_left_y), size);
     }
 
+    // Deactivate a cell at (x, y).
+    #[wasm_bindgen]
+    pub fn deactivate_cell(&mut self, x: u32, y: u32) -> Result<(), String> {
+        self.engine_mut().deactivate_cell(x, y)
+    }
+
+    // Flips the cell at (x, y) and returns whether it is alive now.
+    #[wasm_bindgen]
+    pub fn toggle_cell(&mut self, x: u32, y: u32) -> Result<bool, String> {
+        if self.engine.is_cell_alive(x, y) {
+            self.deactivate_cell(x, y)?;
+            Ok(false)
+        } else {
+            self.activate_cell(x, y)?;
+            Ok(true)
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn is_cell_alive(&self, x: u32, y: u32) -> bool {
+        self.engine.is_cell_alive(x, y)
+    }
+
+    // Kills every cell. The generation keeps counting.
+    #[wasm_bindgen]
+    pub fn clear(&mut self) {
+        let grid = Region::new(0, 0, self.engine.get_cols(), self.engine.get_rows());
+        self.engine_mut().clear_region(&grid);
+    }
+
+    // Gets the number of steps taken since the engine was created.
+    #[wasm_bindgen]
+    pub fn get_generation(&self) -> u64 {
+        self.engine.get_generation()
+    }
+
+    // Stamps an RLE pattern with its top left corner at (x, y), skipping cells outside the grid,
+    // and switches to the rule from its header, if any.
+    #[wasm_bindgen]
+    pub fn load_rle(&mut self, text: &str, x: u32, y: u32) -> Result<(), String> {
+        let document = format::parse_rle(text)?;
+        let engine = self.engine_mut();
+        if let Some(rule) = document.rule {
+            engine.set_rule(rule);
+        }
+        engine.stamp_pattern(&document.pattern, Cell::new(x, y));
+        Ok(())
+    }
+
+    // Gets the alive cells as an RLE pattern with the engine's rule, starting at the top left
+    // corner of their bounding box.
+    #[wasm_bindgen]
+    pub fn save_rle(&self) -> String {
+        let mut document = PatternDocument::new(self.engine.to_pattern().1);
+        document.rule = Some(self.engine.get_rule());
+        format::write_rle(&document)
+    }
+
     // Gets the amount of currently alive cells
     #[wasm_bindgen]
     pub fn get_alive_cells_count(&self) -> usize {
         self.engine.get_alive_cells_count()
     }
 
+    // Gets the alive cells as a flat [x0, y0, x1, y1, ...] view into WASM memory, without copying.
+    // The view is only valid until the next call into the engine, which may move or grow the memory.
+    #[wasm_bindgen]
+    pub fn alive_cells_view(&mut self) -> Uint32Array {
+        self.refresh_alive_cells();
+        // Safety: nothing allocates between creating the view and handing it to JS.
+        unsafe { Uint32Array::view(&self.alive_cells) }
+    }
+
+    // Gets the address of the flat alive cells array in WASM memory, for building a view on
+    // `memory.buffer` directly, e.g. to upload it to WebGL.
+    #[wasm_bindgen]
+    pub fn alive_cells_ptr(&mut self) -> *const u32 {
+        self.refresh_alive_cells();
+        self.alive_cells.as_ptr()
+    }
+
+    // Gets the number of values in the flat alive cells array, twice the number of alive cells.
+    #[wasm_bindgen]
+    pub fn alive_cells_len(&mut self) -> usize {
+        self.refresh_alive_cells();
+        self.alive_cells.len()
+    }
+
+    // Rasterises the viewport of `width`×`height` cells whose top left cell is (x, y) into an RGBA
+    // buffer in WASM memory, with every cell drawn as a `scale`×`scale` square, and returns a view
+    // on it, ready for `new ImageData(view, width * scale, height * scale)`. Colours are "#rrggbb"
+    // or "#rrggbbaa" strings. The view is only valid until the next call into the engine.
+    // When only a step happened since the last call with the same settings, just the cells
+    // that changed are redrawn.
+    #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_viewport(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        scale: u32,
+        background: &str,
+        alive: &str,
+    ) -> Result<Uint8ClampedArray, String> {
+        let settings = FrameSettings {
+            viewport: Region::new(x, y, width, height),
+            scale,
+            background: parse_color(background)?,
+            alive: parse_color(alive)?,
+        };
+        let frame = self.render_frame(settings)?;
+        // Safety: nothing allocates between creating the view and handing it to JS.
+        Ok(unsafe { Uint8ClampedArray::view(frame.pixels()) })
+    }
+
     // Iterates over cells and applies JS function to them
     #[wasm_bindgen]
     pub fn for_each_cell_do(&mut self, callback: &Function) {
@@ -65,4 +235,275 @@ impl LifeEngineWrapper {
             let _ = callback.call2(&JsValue::NULL, &Number::from(c.x), &Number::from(c.y));
         });
     }
+
+    // Enables or disables tracking how many generations each cell has lived.
+    #[wasm_bindgen]
+    pub fn set_age_tracking(&mut self, is_enabled: bool) {
+        self.engine.set_age_tracking(is_enabled);
+    }
+
+    // Enables the heat map with the given decay per generation, or disables it when undefined.
+    #[wasm_bindgen]
+    pub fn set_heat_map(&mut self, decay: Option<f32>) -> Result<(), String> {
+        self.engine.set_heat_map(decay)
+    }
+
+    // Iterates over cells and applies JS function to them with their age as the third argument.
+    // The age is 0 when age tracking is disabled.
+    #[wasm_bindgen]
+    pub fn for_each_cell_with_age_do(&mut self, callback: &Function) {
+        self.engine.get_alive_cells().for_each(|c: &Cell| {
+            let age = self.engine.get_cell_age(c).unwrap_or(0);
+            let _ = callback.call3(
+                &JsValue::NULL,
+                &Number::from(c.x),
+                &Number::from(c.y),
+                &Number::from(age as f64),
+            );
+        });
+    }
+
+    // Iterates over the warm cells of the heat map and applies JS function to them with their heat.
+    #[wasm_bindgen]
+    pub fn for_each_heat_do(&mut self, callback: &Function) {
+        if let Some(heat_map) = self.engine.get_heat_map() {
+            heat_map.iter().for_each(|(c, heat)| {
+                let _ = callback.call3(
+                    &JsValue::NULL,
+                    &Number::from(c.x),
+                    &Number::from(c.y),
+                    &Number::from(heat),
+                );
+            });
+        }
+    }
+
+    // Encodes an animated GIF of the next `frames` generations, leaving the engine untouched.
+    // Colours are "#rrggbb" or "#rrggbbaa" strings; a transparent background makes the GIF
+    // transparent. The animation is cropped to the pattern over the run, or shows the whole grid.
+    #[wasm_bindgen]
+    pub fn export_gif(
+        &self,
+        frames: u32,
+        scale: u32,
+        frame_delay_ms: u32,
+        background: &str,
+        alive: &str,
+        crop_to_pattern: bool,
+    ) -> Result<Vec<u8>, String> {
+        let options = GifOptions {
+            frames,
+            scale,
+            frame_delay_ms,
+            background: parse_color(background)?,
+            alive: parse_color(alive)?,
+            crop: if crop_to_pattern {
+                GifCrop::BoundingBox { margin: 2 }
+            } else {
+                GifCrop::Region(Region::new(
+                    0,
+                    0,
+                    self.engine.get_cols(),
+                    self.engine.get_rows(),
+                ))
+            },
+        };
+        let mut bytes = Vec::new();
+        animation::write_gif(&self.engine, &options, &mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+impl LifeEngineWrapper {
+    fn with_engine(engine: LifeEngine) -> LifeEngineWrapper {
+        LifeEngineWrapper {
+            engine,
+            revision: 0,
+            step_revision: 0,
+            alive_cells: Vec::new(),
+            alive_cells_revision: None,
+            frame: None,
+        }
+    }
+
+    // Borrows the engine to change it, which makes the alive cells array and the frame out of date.
+    fn engine_mut(&mut self) -> &mut LifeEngine {
+        self.revision += 1;
+        &mut self.engine
+    }
+
+    // Brings the cached frame up to date with the engine, or draws a new one for other settings.
+    fn render_frame(&mut self, settings: FrameSettings) -> Result<&Frame, String> {
+        match &mut self.frame {
+            Some((frame, last_settings, revision)) if *last_settings == settings => {
+                if *revision + 1 == self.revision && self.step_revision == self.revision {
+                    frame.apply_last_step(&self.engine);
+                } else if *revision != self.revision {
+                    frame.draw_engine(&self.engine);
+                }
+                *revision = self.revision;
+            }
+            _ => {
+                let mut frame = Frame::new(
+                    settings.viewport,
+                    settings.scale,
+                    settings.background,
+                    settings.alive,
+                )?;
+                frame.draw_engine(&self.engine);
+                self.frame = Some((frame, settings, self.revision));
+            }
+        }
+        let (frame, _, _) = self.frame.as_ref().expect("the frame was just drawn");
+        Ok(frame)
+    }
+
+    // Steps like tick, reading the time in milliseconds from `now`.
+    fn run_for(&mut self, budget_ms: f64, max_steps: u32, mut now: impl FnMut() -> f64) -> u32 {
+        let start = now();
+        let mut steps = 0;
+        while steps < max_steps {
+            self.next();
+            steps += 1;
+            if now() - start >= budget_ms {
+                break;
+            }
+        }
+        steps
+    }
+
+    fn refresh_alive_cells(&mut self) {
+        if self.alive_cells_revision == Some(self.revision) {
+            return;
+        }
+        self.alive_cells.clear();
+        self.alive_cells
+            .extend(self.engine.get_alive_cells().flat_map(|c| [c.x, c.y]));
+        self.alive_cells_revision = Some(self.revision);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BACKGROUND: Rgba = [4, 6, 15, 255];
+    const ALIVE: Rgba = [73, 255, 167, 255];
+
+    fn settings(viewport: Region, scale: u32) -> FrameSettings {
+        FrameSettings {
+            viewport,
+            scale,
+            background: BACKGROUND,
+            alive: ALIVE,
+        }
+    }
+
+    fn full_frame(wrapper: &LifeEngineWrapper, viewport: Region, scale: u32) -> Frame {
+        let mut frame = Frame::new(viewport, scale, BACKGROUND, ALIVE).unwrap();
+        frame.draw_engine(&wrapper.engine);
+        frame
+    }
+
+    // A glider in the top left corner of a 16×16 grid.
+    fn glider() -> LifeEngineWrapper {
+        let mut wrapper = LifeEngineWrapper::new(16, 16);
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            wrapper.activate_cell(x, y).unwrap();
+        }
+        wrapper
+    }
+
+    // A clock that moves `step_ms` milliseconds forward every time it is read.
+    fn clock(step_ms: f64) -> impl FnMut() -> f64 {
+        let mut time = 0.0;
+        move || {
+            time += step_ms;
+            time
+        }
+    }
+
+    #[test]
+    fn tick_runs_at_most_max_steps() {
+        let mut wrapper = glider();
+        assert_eq!(wrapper.run_for(1000.0, 3, clock(1.0)), 3);
+        assert_eq!(wrapper.get_generation(), 3);
+        assert_eq!(wrapper.run_for(1000.0, 0, clock(1.0)), 0);
+        assert_eq!(wrapper.get_generation(), 3);
+    }
+
+    #[test]
+    fn tick_stops_once_the_budget_is_spent() {
+        let mut wrapper = glider();
+        // Every step takes 4 ms of the 10 ms budget, and the third one overruns it.
+        assert_eq!(wrapper.run_for(10.0, 100, clock(4.0)), 3);
+        // A budget spent by the first step still lets that step run.
+        assert_eq!(wrapper.run_for(10.0, 100, clock(50.0)), 1);
+        assert_eq!(wrapper.run_for(0.0, 100, clock(4.0)), 1);
+        assert_eq!(wrapper.get_generation(), 5);
+    }
+
+    #[test]
+    fn frame_has_alive_pixels_at_cell_offsets() {
+        let mut wrapper = glider();
+        let frame = wrapper.render_frame(settings(Region::new(0, 0, 4, 4), 2)).unwrap();
+        assert_eq!((frame.width(), frame.height()), (8, 8));
+        // Cell (1, 0) covers pixels 2..4 of rows 0..2; cell (0, 0) is dead.
+        let offset = 2 * 4;
+        assert_eq!(frame.pixels()[offset..offset + 4], ALIVE);
+        assert_eq!(frame.get_pixel(3, 1), ALIVE);
+        assert_eq!(frame.get_pixel(0, 0), BACKGROUND);
+        assert_eq!(frame.get_pixel(7, 7), BACKGROUND);
+    }
+
+    #[test]
+    fn frame_is_clipped_to_the_viewport() {
+        let mut wrapper = glider();
+        let viewport = Region::new(1, 1, 2, 2);
+        let frame = wrapper.render_frame(settings(viewport, 1)).unwrap();
+        // Only cells (2, 1), (1, 2) and (2, 2) fall inside.
+        assert_eq!(frame.get_pixel(0, 0), BACKGROUND);
+        assert_eq!(frame.get_pixel(1, 0), ALIVE);
+        assert_eq!(frame.get_pixel(0, 1), ALIVE);
+        assert_eq!(frame.get_pixel(1, 1), ALIVE);
+    }
+
+    #[test]
+    fn steps_and_edits_keep_the_frame_up_to_date() {
+        let mut wrapper = glider();
+        let viewport = Region::new(0, 0, 10, 10);
+        wrapper.render_frame(settings(viewport, 3)).unwrap();
+        for generation in 1..=20 {
+            wrapper.next();
+            if generation % 5 == 0 {
+                wrapper.toggle_cell(9, 9).unwrap();
+            }
+            if generation % 7 == 0 {
+                // Two steps in a row can't be applied incrementally.
+                wrapper.next();
+            }
+            let expected = full_frame(&wrapper, viewport, 3);
+            let frame = wrapper.render_frame(settings(viewport, 3)).unwrap();
+            assert_eq!(*frame, expected, "generation {}", generation);
+        }
+    }
+
+    #[test]
+    fn changing_settings_redraws_the_frame() {
+        let mut wrapper = glider();
+        wrapper.render_frame(settings(Region::new(0, 0, 4, 4), 1)).unwrap();
+        wrapper.next();
+        let viewport = Region::new(0, 0, 8, 8);
+        let expected = full_frame(&wrapper, viewport, 2);
+        assert_eq!(*wrapper.render_frame(settings(viewport, 2)).unwrap(), expected);
+    }
+
+    #[test]
+    fn oversized_viewports_are_rejected() {
+        let mut wrapper = glider();
+        let huge = settings(Region::new(0, 0, 70000, 1), 70000);
+        assert!(wrapper.render_frame(huge).is_err());
+        // The previous frame is still usable afterwards.
+        assert!(wrapper.render_frame(settings(Region::new(0, 0, 4, 4), 1)).is_ok());
+    }
 }